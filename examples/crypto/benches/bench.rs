@@ -6,8 +6,8 @@ extern crate try_from;
 use std::fmt;
 
 use criterion::{criterion_group, criterion_main, Bencher, Criterion, Fun};
+use crypto_example::atoms::{Big, BigDfa, BigHash, Small, SmallDfa, SmallHash, TypedSmallMap};
 use crypto_example::{small, small_type, Convention, ConventionVisitor, Price, PriceMapping};
-use crypto_example::atoms::{Big, Small, TypedSmallMap};
 use static_atom::TypedAtomMap;
 use try_from::TryFrom;
 
@@ -26,12 +26,12 @@ fn bench_consts(c: &mut Criterion) {
         struct Visitor<'a> {
             b: &'a mut Bencher,
             expected: i32,
-        };
+        }
 
         impl<'a> ConventionVisitor for Visitor<'a> {
             type Value = ();
 
-            fn visit<C: Convention>(self) -> () {
+            fn visit<C: Convention>(self) {
                 let Visitor { b, expected } = self;
                 assert_eq!(expected, crypto_example::price_digits::<C>());
                 b.iter(|| criterion::black_box(crypto_example::price_digits::<C>()))
@@ -97,8 +97,33 @@ fn bench_parse(c: &mut Criterion) {
     let funs = || -> Vec<Fun<(Result<usize, ()>, &str)>> {
         vec![
             Fun::new("match_keyword", test(match_keyword)),
-            Fun::new("trie_generated_small", test(str::parse::<Small>)),
-            Fun::new("trie_generated_big", test(str::parse::<Big>)),
+            Fun::new("trie_generated_small", test(|s| s.parse::<Small>().map_err(|_| ()))),
+            Fun::new("trie_generated_big", test(|s| s.parse::<Big>().map_err(|_| ()))),
+            Fun::new("phf_generated_small", test(|s| s.parse::<SmallHash>().map_err(|_| ()))),
+            Fun::new("phf_generated_big", test(|s| s.parse::<BigHash>().map_err(|_| ()))),
+            Fun::new("dfa_generated_small", test(|s| s.parse::<SmallDfa>().map_err(|_| ()))),
+            Fun::new("dfa_generated_big", test(|s| s.parse::<BigDfa>().map_err(|_| ()))),
+            Fun::new(
+                "trie_from_bytes_small",
+                test(|s| Small::from_bytes(s.as_bytes()).ok_or(())),
+            ),
+            Fun::new("trie_from_bytes_big", test(|s| Big::from_bytes(s.as_bytes()).ok_or(()))),
+            Fun::new(
+                "phf_from_bytes_small",
+                test(|s| SmallHash::from_bytes(s.as_bytes()).ok_or(())),
+            ),
+            Fun::new(
+                "phf_from_bytes_big",
+                test(|s| BigHash::from_bytes(s.as_bytes()).ok_or(())),
+            ),
+            Fun::new(
+                "dfa_from_bytes_small",
+                test(|s| SmallDfa::from_bytes(s.as_bytes()).ok_or(())),
+            ),
+            Fun::new(
+                "dfa_from_bytes_big",
+                test(|s| BigDfa::from_bytes(s.as_bytes()).ok_or(())),
+            ),
         ]
     };
 
@@ -2,32 +2,13 @@ extern crate static_atom_build;
 
 use std::env;
 use std::error::Error;
-use std::fs::File;
 use std::path::Path;
 use std::result::Result;
 
-fn main() -> Result<(), Box<Error>> {
-    let filename = Path::new(&env::var("OUT_DIR")?).join("atoms.rs");
-    let mut file = File::create(filename)?;
+use static_atom_build::AtomSet;
 
-    static_atom_build::generate(
-        &mut file,
-        "atoms",
-        "Small",
-        vec!["BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC"],
-        vec!["Convention"],
-    )?;
-
-    static_atom_build::generate(
-        &mut file,
-        "atoms",
-        "Big",
-        vec![
-            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "ETH-USDC", "ETC-BTC", "ETC-EUR", "BTC-USD", "BCH-BTC",
-            "BCH-USD", "BTC-GBP", "ETH-USD", "LTC-BTC", "LTC-EUR", "LTC-USD", "BCH-EUR", "ETC-USD", "ZRX-USD",
-            "ZRX-BTC", "ZRX-EUR", "ETC-GBP", "ETH-GBP", "LTC-GBP", "BCH-GBP",
-        ],
-        vec![],
-    )?;
-    Ok(())
+fn main() -> Result<(), Box<dyn Error>> {
+    let out_dir = env::var("OUT_DIR")?;
+    AtomSet::load("build/small.toml")?.generate_to(Path::new(&out_dir).join("atoms.rs"))?;
+    AtomSet::load("build/weighted.toml")?.generate_to(Path::new(&out_dir).join("weighted.rs"))
 }
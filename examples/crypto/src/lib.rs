@@ -18,9 +18,70 @@ pub trait ConventionVisitor {
 
 #[macro_use]
 pub mod atoms {
+    use static_atom_macros::{static_atom, StaticAtom};
+
     use super::{Convention, ConventionVisitor};
 
     include!(concat!(env!("OUT_DIR"), "/atoms.rs"));
+    include!(concat!(env!("OUT_DIR"), "/weighted.rs"));
+
+    static_atom! {
+        #[module(atoms)]
+        pub enum Big {
+            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "ETH-USDC", "ETC-BTC", "ETC-EUR", "BTC-USD", "BCH-BTC",
+            "BCH-USD", "BTC-GBP", "ETH-USD", "LTC-BTC", "LTC-EUR", "LTC-USD", "BCH-EUR", "ETC-USD", "ZRX-USD",
+            "ZRX-BTC", "ZRX-EUR", "ETC-GBP", "ETH-GBP", "LTC-GBP", "BCH-GBP",
+        }
+
+        #[module(atoms)]
+        #[backend(perfect_hash)]
+        pub enum SmallHash {
+            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC",
+        }
+
+        #[module(atoms)]
+        #[backend(dfa)]
+        pub enum SmallDfa {
+            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC",
+        }
+
+        #[module(atoms)]
+        #[backend(dfa)]
+        pub enum BigDfa {
+            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "ETH-USDC", "ETC-BTC", "ETC-EUR", "BTC-USD", "BCH-BTC",
+            "BCH-USD", "BTC-GBP", "ETH-USD", "LTC-BTC", "LTC-EUR", "LTC-USD", "BCH-EUR", "ETC-USD", "ZRX-USD",
+            "ZRX-BTC", "ZRX-EUR", "ETC-GBP", "ETH-GBP", "LTC-GBP", "BCH-GBP",
+        }
+
+        #[module(atoms)]
+        #[backend(perfect_hash)]
+        pub enum BigHash {
+            "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "ETH-USDC", "ETC-BTC", "ETC-EUR", "BTC-USD", "BCH-BTC",
+            "BCH-USD", "BTC-GBP", "ETH-USD", "LTC-BTC", "LTC-EUR", "LTC-USD", "BCH-EUR", "ETC-USD", "ZRX-USD",
+            "ZRX-BTC", "ZRX-EUR", "ETC-GBP", "ETH-GBP", "LTC-GBP", "BCH-GBP",
+        }
+    }
+
+    #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Debug, StaticAtom)]
+    pub enum Currency {
+        #[atom = "BTC"]
+        Bitcoin,
+        #[atom = "ETH"]
+        Ether,
+        #[atom = "EUR"]
+        Euro,
+        #[atom = "USDC"]
+        UsdCoin,
+    }
+
+    // Deliberately not `Copy` or `Clone`, so that generated code which uses an atom twice fails to build.
+    #[derive(StaticAtom)]
+    pub enum Side {
+        #[atom = "buy"]
+        Buy,
+        #[atom = "sell"]
+        Sell,
+    }
 
     impl Convention for small_type!("BTC-EUR") {}
 
@@ -65,7 +126,7 @@ where
     }
 }
 
-impl<'a, C> From<&'a Price<C>> for f64
+impl<C> From<&Price<C>> for f64
 where
     C: Convention,
 {
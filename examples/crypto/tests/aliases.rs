@@ -0,0 +1,71 @@
+mod sets {
+    use static_atom_macros::static_atom;
+
+    static_atom! {
+        #[module(sets)]
+        pub enum AliasTrie {
+            "BTC-EUR" | "XBT-EUR" | "BTC/EUR", "ETH-EUR",
+        }
+
+        #[module(sets)]
+        #[backend(perfect_hash)]
+        pub enum AliasHash {
+            "BTC-EUR" | "XBT-EUR" | "BTC/EUR", "ETH-EUR",
+        }
+
+        #[module(sets)]
+        #[backend(dfa)]
+        pub enum AliasDfa {
+            "BTC-EUR" | "XBT-EUR" | "BTC/EUR", "ETH-EUR",
+        }
+    }
+}
+
+use sets::{AliasDfa, AliasHash, AliasTrie};
+
+macro_rules! check {
+    ($($set:ident),*) => {
+        $(
+            for &alias in ["BTC-EUR", "XBT-EUR", "BTC/EUR"].iter() {
+                let atom = $set::from_bytes(alias.as_bytes()).unwrap();
+                assert_eq!("BTC-EUR", atom.as_str(), "{} {}", stringify!($set), alias);
+                assert_eq!("BTC-EUR", atom.to_string());
+                assert_eq!(Ok(atom), alias.parse::<$set>());
+                assert_eq!(Some((atom, &b" 6000"[..])), $set::parse_prefix(format!("{} 6000", alias).as_bytes()));
+            }
+
+            assert_eq!(None, $set::from_bytes(b"XBT-USD"));
+            assert_eq!(Some("ETH-EUR"), $set::from_bytes(b"ETH-EUR").map(|atom| atom.as_str()));
+        )*
+    };
+}
+
+#[test]
+fn canonical() {
+    check!(AliasTrie, AliasHash, AliasDfa);
+}
+
+#[test]
+fn macros() {
+    assert_eq!(alias_trie!("BTC-EUR"), alias_trie!("XBT-EUR"));
+    assert_eq!("BTC-EUR", alias_trie!("BTC/EUR").as_str());
+}
+
+#[test]
+fn serde() {
+    assert_eq!(
+        alias_trie!("BTC-EUR"),
+        serde_json::from_str::<AliasTrie>("\"XBT-EUR\"").unwrap()
+    );
+    assert_eq!("\"BTC-EUR\"", serde_json::to_string(&alias_trie!("XBT-EUR")).unwrap());
+}
+
+#[test]
+fn loaded() {
+    use crypto_example::atoms::Weighted;
+
+    assert_eq!(
+        Some("BTC-EUR"),
+        Weighted::from_bytes(b"XBT-EUR").map(|atom| atom.as_str())
+    );
+}
@@ -0,0 +1,198 @@
+//! Checks that the trie, perfect hash and DFA backends agree with a naive search over the spellings, for `from_bytes`
+//! and `parse_prefix`, on valid input, truncated input, over-long input and input with any one byte replaced.
+
+// Declares the same atoms once for each backend, along with a list of their spellings for the reference.
+macro_rules! sets {
+    ($spellings:ident, [$($attr:tt)*], $trie:ident, $hash:ident, $dfa:ident, { $($atom:tt),* $(,)? }) => {
+        pub const $spellings: &[&str] = &[$($atom),*];
+
+        static_atom! {
+            #[module(sets)]
+            $($attr)*
+            pub enum $trie { $($atom),* }
+
+            #[module(sets)]
+            #[backend(perfect_hash)]
+            $($attr)*
+            pub enum $hash { $($atom),* }
+
+            #[module(sets)]
+            #[backend(dfa)]
+            $($attr)*
+            pub enum $dfa { $($atom),* }
+        }
+    };
+}
+
+mod sets {
+    use static_atom_macros::static_atom;
+
+    sets!(PAIRS, [], PairsTrie, PairsHash, PairsDfa, {
+        "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "ETH-USDC", "ETC-BTC", "ETC-EUR", "BTC-USD", "BCH-BTC",
+        "BCH-USD", "BTC-GBP", "ETH-USD", "LTC-BTC", "LTC-EUR", "LTC-USD", "BCH-EUR", "ETC-USD", "ZRX-USD",
+        "ZRX-BTC", "ZRX-EUR", "ETC-GBP", "ETH-GBP", "LTC-GBP", "BCH-GBP",
+    });
+
+    sets!(PREFIXES, [], PrefixesTrie, PrefixesHash, PrefixesDfa, {
+        "a", "ab", "abc", "abcd", "abcdefgh", "abcdefghi", "BTC", "BTC-EUR", "BTC-EURC",
+        "a-spelling-longer-than-a-vector-load-of-thirty-two-bytes",
+    });
+
+    // Spellings that differ in a single byte, mostly the last, at lengths matched with one word load and lengths
+    // that need a decision tree over several loads.
+    sets!(SIBLINGS, [], SiblingsTrie, SiblingsHash, SiblingsDfa, {
+        "ETC-EUR", "ETH-EUR", "ETH-EUS", "p", "q", "BTC-USDC", "BTC-USDT", "ABCDEFGHIJKLMNOPQ", "ABCDEFGHIJKLMNOPR",
+    });
+
+    // Folded bytes share a class in the DFA's `CLASSES` table.
+    sets!(FOLDED, [#[case_insensitive] #[equivalent("-/_")]], FoldedTrie, FoldedHash, FoldedDfa, {
+        "BTC-EUR", "eth/btc", "Ltc_Usd", "a-b", "A-B-C", "zrx", "ZRX-", "long-folded/spelling_over-16",
+    });
+}
+
+use sets::*;
+
+// Parses the way the generated code should: the input must equal a spelling, or start with one for `parse_prefix`,
+// comparing bytes after `fold`.
+struct Reference {
+    spellings: &'static [&'static str],
+    fold: fn(u8) -> u8,
+}
+
+impl Reference {
+    fn matches(&self, s: &[u8], spelling: &str) -> bool {
+        s.len() == spelling.len()
+            && s.iter()
+                .zip(spelling.bytes())
+                .all(|(&a, b)| (self.fold)(a) == (self.fold)(b))
+    }
+
+    fn parse(&self, s: &[u8]) -> Option<&'static str> {
+        self.spellings
+            .iter()
+            .cloned()
+            .find(|spelling| self.matches(s, spelling))
+    }
+
+    fn parse_prefix(&self, s: &[u8]) -> Option<(&'static str, usize)> {
+        self.spellings
+            .iter()
+            .cloned()
+            .filter(|spelling| spelling.len() <= s.len() && self.matches(&s[..spelling.len()], spelling))
+            .max_by_key(|spelling| spelling.len())
+            .map(|spelling| (spelling, s.len() - spelling.len()))
+    }
+
+    // Every spelling, each truncation and extension of it, and every way of replacing one of its bytes.
+    fn inputs(&self) -> Vec<Vec<u8>> {
+        let mut inputs = vec![Vec::new()];
+        for spelling in self.spellings.iter() {
+            let bytes = spelling.as_bytes();
+            for len in 0..=bytes.len() {
+                inputs.push(bytes[..len].to_vec());
+            }
+
+            for suffix in [&b"X"[..], b"-", b" 6000", bytes].iter() {
+                inputs.push([bytes, suffix].concat());
+            }
+
+            for i in 0..bytes.len() {
+                for b in 0..=255 {
+                    let mut flipped = bytes.to_vec();
+                    flipped[i] = b;
+                    inputs.push(flipped);
+                }
+            }
+        }
+
+        inputs
+    }
+
+    fn check(
+        &self,
+        backend: &str,
+        from_bytes: impl Fn(&[u8]) -> Option<&'static str>,
+        parse_prefix: impl Fn(&[u8]) -> Option<(&'static str, usize)>,
+    ) {
+        for input in self.inputs() {
+            let text = String::from_utf8_lossy(&input);
+            assert_eq!(
+                self.parse(&input),
+                from_bytes(&input),
+                "{} from_bytes {:?}",
+                backend,
+                text
+            );
+            assert_eq!(
+                self.parse_prefix(&input),
+                parse_prefix(&input),
+                "{} parse_prefix {:?}",
+                backend,
+                text
+            );
+        }
+    }
+}
+
+fn exact(b: u8) -> u8 {
+    b
+}
+
+fn folded(b: u8) -> u8 {
+    match b.to_ascii_lowercase() {
+        b'/' | b'_' => b'-',
+        b => b,
+    }
+}
+
+macro_rules! check {
+    ($reference:expr, $($backend:ident),*) => {
+        $(
+            $reference.check(
+                stringify!($backend),
+                |s| $backend::from_bytes(s).map(|atom| atom.as_str()),
+                |s| $backend::parse_prefix(s).map(|(atom, rest)| (atom.as_str(), rest.len())),
+            );
+        )*
+    };
+}
+
+#[test]
+fn pairs() {
+    let reference = Reference {
+        spellings: PAIRS,
+        fold: exact,
+    };
+
+    check!(reference, PairsTrie, PairsHash, PairsDfa);
+}
+
+#[test]
+fn prefixes() {
+    let reference = Reference {
+        spellings: PREFIXES,
+        fold: exact,
+    };
+
+    check!(reference, PrefixesTrie, PrefixesHash, PrefixesDfa);
+}
+
+#[test]
+fn folded_bytes() {
+    let reference = Reference {
+        spellings: FOLDED,
+        fold: folded,
+    };
+
+    check!(reference, FoldedTrie, FoldedHash, FoldedDfa);
+}
+
+#[test]
+fn siblings() {
+    let reference = Reference {
+        spellings: SIBLINGS,
+        fold: exact,
+    };
+
+    check!(reference, SiblingsTrie, SiblingsHash, SiblingsDfa);
+}
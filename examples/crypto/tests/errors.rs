@@ -0,0 +1,14 @@
+use crypto_example::atoms::Small;
+
+#[test]
+fn typo() {
+    let error = "BTC-EUX".parse::<Small>().unwrap_err();
+    assert_eq!("BTC-EUX", error.input());
+    assert_eq!("Small", error.set());
+    assert_eq!(6, error.offset());
+    assert_eq!(Some("BTC-EUR"), error.suggestion());
+    assert_eq!(
+        "can't parse \"BTC-EUX\" as Small: unexpected byte at offset 6; did you mean \"BTC-EUR\"?",
+        error.to_string()
+    );
+}
@@ -0,0 +1,46 @@
+//! Checks that `static_atom!` passes doc comments, `#[allow]` and `#[cfg]` through to the generated items.
+
+mod sets {
+    use static_atom_macros::static_atom;
+
+    static_atom! {
+        /// Order types accepted by the exchange.
+        #[module(sets)]
+        #[allow(missing_docs)]
+        pub enum Documented {
+            /// Fills at the best available price.
+            Market = "market",
+            /// Fills at the limit price or better.
+            #[allow(non_camel_case_types)]
+            limit = "limit",
+        }
+
+        #[module(sets)]
+        #[cfg(all())]
+        pub enum Gated {
+            "enabled",
+        }
+
+        // Would clash with the set above if the `#[cfg]` did not remove all of its items.
+        #[module(sets)]
+        #[cfg(not(all()))]
+        pub enum Gated {
+            "disabled",
+        }
+    }
+}
+
+use sets::*;
+
+#[test]
+fn documented() {
+    assert_eq!(Some(Documented::Market), Documented::from_bytes(b"market"));
+    assert_eq!(Some(Documented::limit), Documented::from_bytes(b"limit"));
+    assert_eq!("limit", documented!("limit").as_str());
+}
+
+#[test]
+fn gated() {
+    assert_eq!(Some(gated!("enabled")), Gated::from_bytes(b"enabled"));
+    assert_eq!(None, Gated::from_bytes(b"disabled"));
+}
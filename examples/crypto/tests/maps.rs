@@ -0,0 +1,99 @@
+use crypto_example::atoms::{Side, SideMap, SmallMap};
+use crypto_example::small;
+use static_atom::entry::Entry;
+use static_atom::AtomMap;
+
+#[test]
+fn entry_without_copy() {
+    let mut volumes = SideMap::new();
+    *volumes.entry(Side::Buy).or_insert(0) += 5;
+    *volumes.entry(Side::Buy).or_insert(0) += 2;
+    assert_eq!(Some(&7), volumes.get(Side::Buy));
+
+    match volumes.entry(Side::Sell) {
+        Entry::Vacant(entry) => assert!(matches!(entry.key(), Side::Sell)),
+        Entry::Occupied(_) => panic!("expected a vacant entry"),
+    }
+}
+
+#[test]
+fn extend() {
+    let mut map = SmallMap::new();
+    map.extend(vec![(small!("BTC-EUR"), 1), (small!("ETH-BTC"), 2)]);
+    AtomMap::extend(&mut map, vec![(small!("ETH-BTC"), 3)]);
+    Extend::extend(&mut map, vec![(small!("ETH-EUR"), 4)]);
+    assert_eq!(
+        vec![
+            (small!("BTC-EUR"), &1),
+            (small!("ETH-EUR"), &4),
+            (small!("ETH-BTC"), &3)
+        ],
+        map.iter().collect::<Vec<_>>()
+    );
+}
+
+#[derive(Debug, Default)]
+struct Volumes {
+    by_pair: SmallMap<u32>,
+}
+
+const EMPTY: SmallMap<u32> = SmallMap::new();
+
+#[test]
+fn default_and_debug() {
+    let mut volumes = Volumes::default();
+    assert!(volumes.by_pair.is_empty());
+    assert_eq!(EMPTY, volumes.by_pair);
+    assert_eq!("Volumes { by_pair: {} }", format!("{:?}", volumes));
+
+    volumes.by_pair.insert(small!("ETH-BTC"), 2);
+    volumes.by_pair.insert(small!("BTC-EUR"), 1);
+    assert_eq!(
+        "Volumes { by_pair: {\"BTC-EUR\": 1, \"ETH-BTC\": 2} }",
+        format!("{:?}", volumes)
+    );
+}
+
+#[test]
+fn index() {
+    let mut map = SmallMap::new();
+    map.insert(small!("BTC-EUR"), 1);
+    assert_eq!(1, map[small!("BTC-EUR")]);
+
+    map[small!("BTC-EUR")] += 10;
+    assert_eq!(Some(&11), map.get(small!("BTC-EUR")));
+}
+
+#[test]
+#[should_panic(expected = "no entry found for key")]
+fn index_missing() {
+    let map = SmallMap::<u32>::new();
+    let _ = map[small!("ETH-EUR")];
+}
+
+#[test]
+#[should_panic(expected = "no entry found for key")]
+fn index_mut_missing() {
+    let mut map = SmallMap::<u32>::new();
+    map[small!("ETH-EUR")] = 1;
+}
+
+#[test]
+fn into_iter() {
+    let mut map = vec![(small!("ETH-BTC"), 3), (small!("BTC-EUR"), 1)]
+        .into_iter()
+        .collect::<SmallMap<u32>>();
+
+    for (_, value) in &mut map {
+        *value *= 2;
+    }
+
+    assert_eq!(
+        vec![(small!("BTC-EUR"), &2), (small!("ETH-BTC"), &6)],
+        (&map).into_iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![(small!("BTC-EUR"), 2), (small!("ETH-BTC"), 6)],
+        map.into_iter().collect::<Vec<_>>()
+    );
+}
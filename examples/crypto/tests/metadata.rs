@@ -0,0 +1,26 @@
+use crypto_example::atoms::Small;
+use crypto_example::small;
+
+const ETH_BTC_LOT_SIZE: u32 = small!("ETH-BTC").lot_size();
+
+#[test]
+fn values() {
+    let table = [
+        ("BTC-EUR", 0.01, 1, "EUR"),
+        ("BTC-USDC", 0.01, 1, "USDC"),
+        ("ETH-EUR", 0.01, 10, "EUR"),
+        ("ETH-BTC", 1e-5, 10, "BTC"),
+    ];
+
+    for &(atom, tick_size, lot_size, quote) in table.iter() {
+        let atom = atom.parse::<Small>().unwrap();
+        assert_eq!(tick_size, atom.tick_size(), "{}", atom);
+        assert_eq!(lot_size, atom.lot_size(), "{}", atom);
+        assert_eq!(quote, atom.quote(), "{}", atom);
+    }
+}
+
+#[test]
+fn const_context() {
+    assert_eq!(10, ETH_BTC_LOT_SIZE);
+}
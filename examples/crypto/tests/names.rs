@@ -0,0 +1,29 @@
+mod sets {
+    use static_atom_macros::static_atom;
+
+    static_atom! {
+        #[module(sets)]
+        #[named_variants]
+        pub enum Named {
+            "BTC-EUR", "1INCH-EUR", EtherBitcoin = "ETH-BTC",
+        }
+    }
+}
+
+use sets::Named;
+
+#[test]
+fn named_variants() {
+    assert_eq!(Some(Named::BtcEur), Named::from_bytes(b"BTC-EUR"));
+    assert_eq!(Some(Named::_1InchEur), Named::from_bytes(b"1INCH-EUR"));
+    assert_eq!(Some(Named::EtherBitcoin), Named::from_bytes(b"ETH-BTC"));
+    assert_eq!(named!("ETH-BTC"), Named::EtherBitcoin);
+}
+
+#[test]
+fn constants() {
+    assert_eq!(Named::BtcEur, Named::BTC_EUR);
+    assert_eq!(Named::_1InchEur, Named::_1INCH_EUR);
+    assert_eq!(Named::EtherBitcoin, Named::ETHER_BITCOIN);
+    assert_eq!("1INCH-EUR", Named::_1INCH_EUR.as_str());
+}
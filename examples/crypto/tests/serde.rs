@@ -0,0 +1,129 @@
+use crypto_example::atoms::{Small, SmallMap, TypedSmallMap};
+use crypto_example::{small, small_type};
+use serde::de::value::{BytesDeserializer, Error, StrDeserializer, StringDeserializer};
+use serde::Deserialize;
+use static_atom::{AtomMap, Mapping, TypedAtomMap};
+
+mod lax {
+    use static_atom_macros::static_atom;
+
+    static_atom! {
+        #[module(lax)]
+        #[ignore_unknown_keys]
+        pub enum Lax {
+            "BTC-EUR", "ETH-EUR",
+        }
+    }
+}
+
+use lax::{LaxMap, TypedLaxMap};
+
+struct Sizes;
+
+impl<A> Mapping<A> for Sizes {
+    type Value = u32;
+}
+
+#[test]
+fn map() {
+    let mut map = SmallMap::new();
+    map.insert(small!("ETH-BTC"), 10);
+    map.insert(small!("BTC-EUR"), 1);
+
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!("{\"BTC-EUR\":1,\"ETH-BTC\":10}", json);
+    assert_eq!(map, serde_json::from_str(&json).unwrap());
+    assert_eq!(SmallMap::<u32>::new(), serde_json::from_str("{}").unwrap());
+}
+
+#[test]
+fn map_unknown_key() {
+    let error = serde_json::from_str::<SmallMap<u32>>("{\"BTC-EUR\":1,\"LTC-EUR\":2}").unwrap_err();
+    assert!(
+        error.to_string().contains("can't parse \"LTC-EUR\" as Small"),
+        "{}",
+        error
+    );
+
+    let map = serde_json::from_str::<LaxMap<u32>>("{\"BTC-EUR\":1,\"LTC-EUR\":2}").unwrap();
+    assert_eq!(vec![(lax!("BTC-EUR"), &1)], map.iter().collect::<Vec<_>>());
+}
+
+#[test]
+fn typed_map() {
+    let mut map = TypedSmallMap::<Sizes>::new();
+    map.insert::<small_type!("ETH-BTC")>(10);
+    map.insert::<small_type!("BTC-EUR")>(1);
+
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!("{\"BTC-EUR\":1,\"ETH-BTC\":10}", json);
+
+    let map = serde_json::from_str::<TypedSmallMap<Sizes>>(&json).unwrap();
+    assert_eq!(Some(&1), map.get::<small_type!("BTC-EUR")>());
+    assert_eq!(None, map.get::<small_type!("ETH-EUR")>());
+    assert_eq!(Some(&10), map.get::<small_type!("ETH-BTC")>());
+}
+
+#[test]
+fn typed_map_unknown_key() {
+    let json = "{\"ETH-EUR\":3,\"LTC-EUR\":2}";
+    let error = serde_json::from_str::<TypedSmallMap<Sizes>>(json).err().unwrap();
+    assert!(
+        error.to_string().contains("can't parse \"LTC-EUR\" as Small"),
+        "{}",
+        error
+    );
+
+    let map = serde_json::from_str::<TypedLaxMap<Sizes>>(json).unwrap();
+    assert_eq!(Some(&3), map.get::<lax_type!("ETH-EUR")>());
+    assert_eq!(None, map.get::<lax_type!("BTC-EUR")>());
+}
+
+#[test]
+fn atom_from_str() {
+    assert_eq!(small!("BTC-EUR"), serde_json::from_str::<Small>("\"BTC-EUR\"").unwrap());
+    assert_eq!(
+        Ok(small!("ETH-EUR")),
+        Small::deserialize(StrDeserializer::<Error>::new("ETH-EUR"))
+    );
+}
+
+#[test]
+fn atom_from_string() {
+    // An escape means serde_json can't lend a slice of the input, and a reader never can.
+    assert_eq!(
+        small!("BTC-EUR"),
+        serde_json::from_str::<Small>("\"BTC\\u002dEUR\"").unwrap()
+    );
+    assert_eq!(
+        small!("ETH-BTC"),
+        serde_json::from_reader::<_, Small>(&b"\"ETH-BTC\""[..]).unwrap()
+    );
+    assert_eq!(
+        Ok(small!("ETH-EUR")),
+        Small::deserialize(StringDeserializer::<Error>::new("ETH-EUR".to_owned()))
+    );
+}
+
+#[test]
+fn atom_from_bytes() {
+    assert_eq!(
+        Ok(small!("BTC-USDC")),
+        Small::deserialize(BytesDeserializer::<Error>::new(b"BTC-USDC"))
+    );
+}
+
+#[test]
+fn atom_unknown() {
+    let message = "can't parse \"BTC-EUX\" as Small: unexpected byte at offset 6; did you mean \"BTC-EUR\"?";
+    let error = serde_json::from_str::<Small>("\"BTC-EUX\"").unwrap_err();
+    assert!(error.to_string().starts_with(message), "{}", error);
+
+    let error = Small::deserialize(StrDeserializer::<Error>::new("BTC-EUX")).unwrap_err();
+    assert_eq!(message, error.to_string());
+
+    let error = Small::deserialize(BytesDeserializer::<Error>::new(b"BTC-EUX")).unwrap_err();
+    assert_eq!(message, error.to_string());
+
+    assert!(serde_json::from_str::<Small>("1").is_err());
+}
@@ -0,0 +1,112 @@
+use crypto_example::atoms::{BigSet, SmallSet};
+use crypto_example::{big, small};
+
+const PAIRS: SmallSet = SmallSet::new().with(small!("ETH-BTC")).with(small!("BTC-EUR"));
+
+#[test]
+fn constructors() {
+    assert!(SmallSet::new().is_empty());
+    assert_eq!(SmallSet::new(), SmallSet::default());
+    assert_eq!(4, SmallSet::all().len());
+    assert_eq!(2, PAIRS.len());
+    assert!(PAIRS.contains(small!("BTC-EUR")));
+    assert!(!PAIRS.contains(small!("ETH-EUR")));
+    assert_eq!(
+        SmallSet::new().with(small!("ETH-BTC")),
+        PAIRS.without(small!("BTC-EUR"))
+    );
+}
+
+#[test]
+fn insert_and_remove() {
+    let mut set = SmallSet::new();
+    assert!(set.insert(small!("ETH-EUR")));
+    assert!(!set.insert(small!("ETH-EUR")));
+    assert!(set.contains(small!("ETH-EUR")));
+    assert!(set.remove(small!("ETH-EUR")));
+    assert!(!set.remove(small!("ETH-EUR")));
+    assert!(set.is_empty());
+
+    set.extend(vec![small!("BTC-EUR"), small!("BTC-USDC")]);
+    set.clear();
+    assert!(set.is_empty());
+}
+
+#[test]
+fn algebra() {
+    let eur = SmallSet::new().with(small!("BTC-EUR")).with(small!("ETH-EUR"));
+    let btc = SmallSet::new()
+        .with(small!("BTC-EUR"))
+        .with(small!("BTC-USDC"))
+        .with(small!("ETH-BTC"));
+
+    assert_eq!(SmallSet::all(), eur | btc);
+    assert_eq!(SmallSet::new().with(small!("BTC-EUR")), eur & btc);
+    assert_eq!(SmallSet::new().with(small!("ETH-EUR")), eur - btc);
+    assert_eq!(
+        SmallSet::new()
+            .with(small!("ETH-EUR"))
+            .with(small!("BTC-USDC"))
+            .with(small!("ETH-BTC")),
+        eur ^ btc
+    );
+    assert_eq!(SmallSet::new().with(small!("BTC-USDC")).with(small!("ETH-BTC")), !eur);
+    assert_eq!(SmallSet::new(), !SmallSet::all());
+
+    let mut set = eur;
+    set |= btc;
+    set &= !PAIRS;
+    set -= SmallSet::new().with(small!("BTC-USDC"));
+    set ^= PAIRS;
+    assert_eq!(SmallSet::new().with(small!("ETH-EUR")) | PAIRS, set);
+
+    assert!(PAIRS.is_subset(&btc));
+    assert!(!eur.is_subset(&btc));
+    assert!(eur.is_disjoint(&SmallSet::new().with(small!("ETH-BTC"))));
+    assert!(!eur.is_disjoint(&btc));
+}
+
+#[test]
+fn iteration_order() {
+    // Atoms come out in declaration order, whatever order they went in.
+    let set = vec![small!("ETH-BTC"), small!("BTC-EUR"), small!("ETH-EUR")]
+        .into_iter()
+        .collect::<SmallSet>();
+
+    assert_eq!(
+        vec![small!("BTC-EUR"), small!("ETH-EUR"), small!("ETH-BTC")],
+        set.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(set.iter().collect::<Vec<_>>(), (&set).into_iter().collect::<Vec<_>>());
+    assert_eq!(set.iter().collect::<Vec<_>>(), set.into_iter().collect::<Vec<_>>());
+
+    let all = BigSet::all().iter().collect::<Vec<_>>();
+    assert_eq!(24, all.len());
+    assert_eq!(
+        (0..24).map(|index| usize::from(all[index])).collect::<Vec<_>>(),
+        (0..24).collect::<Vec<_>>()
+    );
+    assert_eq!(Some(&big!("BCH-GBP")), all.last());
+}
+
+#[test]
+fn debug() {
+    assert_eq!("{\"BTC-EUR\", \"ETH-BTC\"}", format!("{:?}", PAIRS));
+}
+
+#[test]
+fn serde() {
+    assert_eq!("[\"BTC-EUR\",\"ETH-BTC\"]", serde_json::to_string(&PAIRS).unwrap());
+    assert_eq!(
+        PAIRS,
+        serde_json::from_str::<SmallSet>("[\"ETH-BTC\",\"BTC-EUR\"]").unwrap()
+    );
+    assert_eq!(SmallSet::new(), serde_json::from_str::<SmallSet>("[]").unwrap());
+    assert!(serde_json::from_str::<SmallSet>("[\"LTC-EUR\"]").is_err());
+
+    let set = BigSet::new().with(big!("ZRX-USD")).with(big!("BTC-GBP"));
+    assert_eq!(
+        set,
+        serde_json::from_str::<BigSet>(&serde_json::to_string(&set).unwrap()).unwrap()
+    );
+}
@@ -0,0 +1,93 @@
+use crypto_example::atoms::{Small, SmallMap, SmallTable};
+use crypto_example::small;
+use static_atom::{AtomMap, TryFrom};
+
+fn lot_sizes() -> SmallTable<u32> {
+    SmallTable::from_fn(|atom| match atom.as_str() {
+        "BTC-EUR" | "BTC-USDC" => 1,
+        _ => 10,
+    })
+}
+
+#[test]
+fn lookups() {
+    let mut table = lot_sizes();
+    assert_eq!(1, table[small!("BTC-EUR")]);
+    assert_eq!(10, table[small!("ETH-BTC")]);
+
+    table[small!("ETH-BTC")] = 100;
+    assert_eq!(100, table[small!("ETH-BTC")]);
+    assert_eq!(vec![&1, &1, &10, &100], table.values().collect::<Vec<_>>());
+
+    for value in table.values_mut() {
+        *value += 1;
+    }
+
+    assert_eq!(vec![&2, &2, &11, &101], table.values().collect::<Vec<_>>());
+}
+
+#[test]
+fn iteration() {
+    let mut table = lot_sizes();
+    for (atom, value) in table.iter_mut() {
+        if atom == small!("ETH-EUR") {
+            *value = 0;
+        }
+    }
+
+    assert_eq!(
+        vec![
+            (small!("BTC-EUR"), &1),
+            (small!("BTC-USDC"), &1),
+            (small!("ETH-EUR"), &0),
+            (small!("ETH-BTC"), &10),
+        ],
+        table.iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        table.iter().collect::<Vec<_>>(),
+        (&table).into_iter().collect::<Vec<_>>()
+    );
+    assert_eq!(
+        vec![
+            (small!("BTC-EUR"), 1),
+            (small!("BTC-USDC"), 1),
+            (small!("ETH-EUR"), 0),
+            (small!("ETH-BTC"), 10),
+        ],
+        table.into_iter().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn map_and_zip() {
+    let names = SmallTable::from_fn(|atom: Small| atom.as_str().to_owned());
+    let doubled = lot_sizes().map(|size| size * 2);
+    assert_eq!(20, doubled[small!("ETH-EUR")]);
+
+    let zipped = names.zip(doubled);
+    assert_eq!(("ETH-BTC".to_owned(), 20), zipped[small!("ETH-BTC")]);
+}
+
+#[test]
+fn default_and_debug() {
+    assert_eq!(SmallTable::from_fn(|_| 0), SmallTable::<u32>::default());
+    assert_eq!(
+        "{\"BTC-EUR\": 1, \"BTC-USDC\": 1, \"ETH-EUR\": 10, \"ETH-BTC\": 10}",
+        format!("{:?}", lot_sizes())
+    );
+}
+
+#[test]
+fn maps() {
+    let map = SmallMap::from(lot_sizes());
+    assert_eq!(4, map.len());
+    assert_eq!(Some(&10), map.get(small!("ETH-EUR")));
+    assert_eq!(Ok(lot_sizes()), SmallTable::try_from(map));
+
+    let mut map = SmallMap::new();
+    map.insert(small!("BTC-EUR"), 1);
+    map.insert(small!("BTC-USDC"), 1);
+    map.insert(small!("ETH-BTC"), 10);
+    assert_eq!(Err(small!("ETH-EUR")), SmallTable::try_from(map));
+}
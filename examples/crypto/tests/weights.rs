@@ -0,0 +1,53 @@
+//! Checks that weighting a set, which moves its heaviest atoms to a fast path and its lightest out of line, doesn't
+//! change what it parses.
+
+use crypto_example::atoms::Weighted;
+
+const ATOMS: &[&str] = &[
+    "BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC", "BTC-USD", "LTC-EUR", "BCH-GBP", "ZRX-BTC", "ETH-USDC",
+];
+
+#[test]
+fn atoms() {
+    for &atom in ATOMS {
+        assert_eq!(
+            Some(atom),
+            Weighted::from_bytes(atom.as_bytes()).map(|atom| atom.as_str())
+        );
+        assert_eq!(Ok(atom), atom.parse::<Weighted>().map(|atom| atom.as_str()));
+    }
+
+    assert_eq!(
+        Some("BTC-EUR"),
+        Weighted::from_bytes(b"XBT-EUR").map(|atom| atom.as_str())
+    );
+}
+
+#[test]
+fn near_misses() {
+    for &atom in ATOMS {
+        let bytes = atom.as_bytes();
+        for len in 0..bytes.len() {
+            let expected = ATOMS.iter().cloned().find(|&other| other == &atom[..len]);
+            assert_eq!(
+                expected,
+                Weighted::from_bytes(&bytes[..len]).map(|atom| atom.as_str()),
+                "{:?}",
+                &atom[..len]
+            );
+        }
+
+        for i in 0..bytes.len() {
+            let mut flipped = bytes.to_vec();
+            flipped[i] ^= 0x20;
+            assert_eq!(
+                None,
+                Weighted::from_bytes(&flipped),
+                "{:?}",
+                String::from_utf8_lossy(&flipped)
+            );
+        }
+
+        assert_eq!(None, Weighted::from_bytes(format!("{}X", atom).as_bytes()));
+    }
+}
@@ -0,0 +1,300 @@
+use std::io::Write;
+
+use itertools::Itertools;
+
+use crate::{Result, Spec};
+
+/// Generates `{Name}Set`, a set of atoms stored as one bit per atom.
+pub fn generate<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    let Spec {
+        vis,
+        name,
+        ref atoms,
+        std,
+        ..
+    } = *spec;
+
+    let words = atoms.len().div_ceil(64);
+    let all = (0..words)
+        .map(|word| {
+            let bits = (atoms.len() - word * 64).min(64);
+            format!("{:#x}", u64::MAX >> (64 - bits))
+        })
+        .join(", ");
+
+    writeln!(
+        writer,
+        "\
+        #[derive(Copy, Clone, PartialEq, Eq, Hash)]
+        {vis} struct {name}Set([u64; {words}]);
+
+        impl {name}Set {{
+            const fn index(key: {name}) -> usize {{
+                match key {{",
+        vis = vis,
+        name = name,
+        words = words
+    )?;
+
+    for (index, atom) in atoms.iter().enumerate() {
+        writeln!(writer, "{pattern} => {index},", pattern = atom.pattern, index = index)?;
+    }
+
+    writeln!(
+        writer,
+        "\
+                }}
+            }}
+
+            pub const fn new() -> Self {{
+                {name}Set([0; {words}])
+            }}
+
+            pub const fn all() -> Self {{
+                {name}Set([{all}])
+            }}
+
+            pub const fn with(mut self, key: {name}) -> Self {{
+                let index = Self::index(key);
+                self.0[index / 64] |= 1 << (index % 64);
+                self
+            }}
+
+            pub const fn without(mut self, key: {name}) -> Self {{
+                let index = Self::index(key);
+                self.0[index / 64] &= !(1 << (index % 64));
+                self
+            }}
+
+            pub const fn contains(&self, key: {name}) -> bool {{
+                let index = Self::index(key);
+                self.0[index / 64] & (1 << (index % 64)) != 0
+            }}
+
+            pub fn insert(&mut self, key: {name}) -> bool {{
+                let index = Self::index(key);
+                let bit = 1 << (index % 64);
+                let inserted = self.0[index / 64] & bit == 0;
+                self.0[index / 64] |= bit;
+                inserted
+            }}
+
+            pub fn remove(&mut self, key: {name}) -> bool {{
+                let index = Self::index(key);
+                let bit = 1 << (index % 64);
+                let removed = self.0[index / 64] & bit != 0;
+                self.0[index / 64] &= !bit;
+                removed
+            }}
+
+            pub fn clear(&mut self) {{
+                *self = Self::new();
+            }}
+
+            pub const fn len(&self) -> usize {{
+                let mut len = 0;
+                let mut i = 0;
+                while i < self.0.len() {{
+                    len += self.0[i].count_ones() as usize;
+                    i += 1;
+                }}
+
+                len
+            }}
+
+            pub const fn is_empty(&self) -> bool {{
+                self.len() == 0
+            }}
+
+            pub const fn union(mut self, other: Self) -> Self {{
+                let mut i = 0;
+                while i < self.0.len() {{
+                    self.0[i] |= other.0[i];
+                    i += 1;
+                }}
+
+                self
+            }}
+
+            pub const fn intersection(mut self, other: Self) -> Self {{
+                let mut i = 0;
+                while i < self.0.len() {{
+                    self.0[i] &= other.0[i];
+                    i += 1;
+                }}
+
+                self
+            }}
+
+            pub const fn difference(mut self, other: Self) -> Self {{
+                let mut i = 0;
+                while i < self.0.len() {{
+                    self.0[i] &= !other.0[i];
+                    i += 1;
+                }}
+
+                self
+            }}
+
+            pub const fn symmetric_difference(mut self, other: Self) -> Self {{
+                let mut i = 0;
+                while i < self.0.len() {{
+                    self.0[i] ^= other.0[i];
+                    i += 1;
+                }}
+
+                self
+            }}
+
+            pub const fn is_subset(&self, other: &Self) -> bool {{
+                self.difference(*other).is_empty()
+            }}
+
+            pub const fn is_disjoint(&self, other: &Self) -> bool {{
+                self.intersection(*other).is_empty()
+            }}
+
+            pub fn iter(&self) -> ::static_atom::iterators::Bits<{name}, {words}> {{
+                ::static_atom::iterators::Bits::new(self.0)
+            }}
+        }}
+
+        impl Default for {name}Set {{
+            fn default() -> Self {{
+                Self::new()
+            }}
+        }}
+
+        impl {std}::fmt::Debug for {name}Set {{
+            fn fmt(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                f.debug_set().entries(self.iter().map(|key| key.as_str())).finish()
+            }}
+        }}
+
+        impl IntoIterator for {name}Set {{
+            type Item = {name};
+            type IntoIter = ::static_atom::iterators::Bits<{name}, {words}>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                self.iter()
+            }}
+        }}
+
+        impl IntoIterator for &{name}Set {{
+            type Item = {name};
+            type IntoIter = ::static_atom::iterators::Bits<{name}, {words}>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                self.iter()
+            }}
+        }}
+
+        impl {std}::iter::FromIterator<{name}> for {name}Set {{
+            fn from_iter<I: IntoIterator<Item = {name}>>(iter: I) -> Self {{
+                let mut set = Self::new();
+                set.extend(iter);
+                set
+            }}
+        }}
+
+        impl {std}::iter::Extend<{name}> for {name}Set {{
+            fn extend<I: IntoIterator<Item = {name}>>(&mut self, iter: I) {{
+                for key in iter {{
+                    self.insert(key);
+                }}
+            }}
+        }}",
+        name = name,
+        words = words,
+        all = all,
+        std = std
+    )?;
+
+    for &(op, method, set_op) in [
+        ("BitOr", "bitor", "union"),
+        ("BitAnd", "bitand", "intersection"),
+        ("Sub", "sub", "difference"),
+        ("BitXor", "bitxor", "symmetric_difference"),
+    ]
+    .iter()
+    {
+        writeln!(
+            writer,
+            "\
+            impl {std}::ops::{op} for {name}Set {{
+                type Output = Self;
+
+                fn {method}(self, other: Self) -> Self {{
+                    self.{set_op}(other)
+                }}
+            }}
+
+            impl {std}::ops::{op}Assign for {name}Set {{
+                fn {method}_assign(&mut self, other: Self) {{
+                    *self = self.{set_op}(other);
+                }}
+            }}",
+            std = std,
+            name = name,
+            op = op,
+            method = method,
+            set_op = set_op
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+        impl {std}::ops::Not for {name}Set {{
+            type Output = Self;
+
+            fn not(self) -> Self {{
+                Self::all().difference(self)
+            }}
+        }}",
+        std = std,
+        name = name
+    )?;
+
+    #[cfg(feature = "serde")]
+    {
+        writeln!(
+            writer,
+            "\
+            impl ::serde::Serialize for {name}Set {{
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+                    serializer.collect_seq(self.iter().map(|key| key.as_str()))
+                }}
+            }}
+
+            impl<'de> ::serde::Deserialize<'de> for {name}Set {{
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+                    struct Visitor;
+
+                    impl<'de> ::serde::de::Visitor<'de> for Visitor {{
+                        type Value = {name}Set;
+
+                        fn expecting(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                            f.write_str(\"a sequence of {name} atoms\")
+                        }}
+
+                        fn visit_seq<A: ::serde::de::SeqAccess<'de>>(self, mut seq: A) -> Result<{name}Set, A::Error> {{
+                            let mut set = {name}Set::new();
+                            while let Some(key) = seq.next_element::<{name}>()? {{
+                                set.insert(key);
+                            }}
+
+                            Ok(set)
+                        }}
+                    }}
+
+                    deserializer.deserialize_seq(Visitor)
+                }}
+            }}",
+            std = std,
+            name = name
+        )?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,205 @@
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::{FieldType, FieldValue, Result};
+
+/// How the generated `from_bytes` finds the atom for its input.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    any(feature = "toml", feature = "json"),
+    derive(serde::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum Backend {
+    /// Compares the input against a trie of the atoms, several bytes at a time.
+    Trie,
+    /// Steps through a table of byte transitions, one lookup per input byte with no data-dependent branches.
+    /// Suits sets that see a lot of invalid input.
+    Dfa,
+    /// Looks the input up in a perfect hash table computed at build time, then compares it against the one atom it
+    /// could be. Suits large sets with long shared prefixes.
+    PerfectHash,
+}
+
+#[derive(Clone, Debug)]
+pub struct AtomSet {
+    pub(crate) name: String,
+    pub(crate) module: String,
+    pub(crate) atoms: Vec<String>,
+    pub(crate) aliases: Vec<(String, String)>,
+    pub(crate) attributes: Vec<String>,
+    pub(crate) variant_attributes: Vec<(String, String)>,
+    pub(crate) named_variants: bool,
+    pub(crate) variant_names: Vec<(String, String)>,
+    pub(crate) visitors: Vec<String>,
+    pub(crate) case_insensitive: bool,
+    pub(crate) equivalents: Vec<Vec<u8>>,
+    pub(crate) backend: Backend,
+    pub(crate) no_std: bool,
+    pub(crate) ignore_unknown_keys: bool,
+    pub(crate) fields: Vec<(String, FieldType)>,
+    pub(crate) values: Vec<(String, String, FieldValue)>,
+    pub(crate) weights: Vec<(String, u64)>,
+    pub(crate) observed: Vec<(String, u64)>,
+    pub(crate) inputs: Vec<PathBuf>,
+}
+
+impl AtomSet {
+    pub fn new<S: Into<String>>(name: S) -> Self {
+        AtomSet {
+            name: name.into(),
+            module: String::new(),
+            atoms: Vec::new(),
+            aliases: Vec::new(),
+            attributes: Vec::new(),
+            variant_attributes: Vec::new(),
+            named_variants: false,
+            variant_names: Vec::new(),
+            visitors: Vec::new(),
+            case_insensitive: false,
+            equivalents: Vec::new(),
+            backend: Backend::Trie,
+            no_std: false,
+            ignore_unknown_keys: false,
+            fields: Vec::new(),
+            values: Vec::new(),
+            weights: Vec::new(),
+            observed: Vec::new(),
+            inputs: Vec::new(),
+        }
+    }
+
+    pub fn module<S: Into<String>>(mut self, module: S) -> Self {
+        self.module = module.into();
+        self
+    }
+
+    pub fn atom<S: AsRef<str>>(mut self, atom: S) -> Self {
+        self.atoms.push(atom.as_ref().to_owned());
+        self
+    }
+
+    pub fn atoms<I>(mut self, atoms: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.atoms
+            .extend(atoms.into_iter().map(|atom| atom.as_ref().to_owned()));
+        self
+    }
+
+    pub fn alias<S: AsRef<str>, T: AsRef<str>>(mut self, atom: S, alias: T) -> Self {
+        self.aliases.push((atom.as_ref().to_owned(), alias.as_ref().to_owned()));
+        self
+    }
+
+    pub fn attribute<S: AsRef<str>>(mut self, attribute: S) -> Self {
+        self.attributes.push(attribute.as_ref().to_owned());
+        self
+    }
+
+    pub fn variant_attribute<S: AsRef<str>, T: AsRef<str>>(mut self, atom: S, attribute: T) -> Self {
+        self.variant_attributes
+            .push((atom.as_ref().to_owned(), attribute.as_ref().to_owned()));
+        self
+    }
+
+    pub fn named_variants(mut self, named_variants: bool) -> Self {
+        self.named_variants = named_variants;
+        self
+    }
+
+    pub fn variant_name<S: AsRef<str>, T: AsRef<str>>(mut self, atom: S, variant: T) -> Self {
+        self.variant_names
+            .push((atom.as_ref().to_owned(), variant.as_ref().to_owned()));
+        self
+    }
+
+    pub fn visitor<S: AsRef<str>>(mut self, visitor: S) -> Self {
+        self.visitors.push(visitor.as_ref().to_owned());
+        self
+    }
+
+    pub fn visitors<I>(mut self, visitors: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        self.visitors
+            .extend(visitors.into_iter().map(|visitor| visitor.as_ref().to_owned()));
+        self
+    }
+
+    pub fn case_insensitive(mut self, case_insensitive: bool) -> Self {
+        self.case_insensitive = case_insensitive;
+        self
+    }
+
+    pub fn equivalent<B: AsRef<[u8]>>(mut self, bytes: B) -> Self {
+        self.equivalents.push(bytes.as_ref().to_owned());
+        self
+    }
+
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    pub fn no_std(mut self, no_std: bool) -> Self {
+        self.no_std = no_std;
+        self
+    }
+
+    pub fn ignore_unknown_keys(mut self, ignore_unknown_keys: bool) -> Self {
+        self.ignore_unknown_keys = ignore_unknown_keys;
+        self
+    }
+
+    pub fn field<S: AsRef<str>>(mut self, field: S, ty: FieldType) -> Self {
+        self.fields.push((field.as_ref().to_owned(), ty));
+        self
+    }
+
+    pub fn value<S: AsRef<str>, T: AsRef<str>, V: Into<FieldValue>>(mut self, atom: S, field: T, value: V) -> Self {
+        self.values
+            .push((atom.as_ref().to_owned(), field.as_ref().to_owned(), value.into()));
+        self
+    }
+
+    pub fn weight<S: AsRef<str>>(mut self, atom: S, weight: u64) -> Self {
+        self.weights.push((atom.as_ref().to_owned(), weight));
+        self
+    }
+
+    pub(crate) fn observe<S: Into<String>>(mut self, input: S, count: u64) -> Self {
+        self.observed.push((input.into(), count));
+        self
+    }
+
+    pub(crate) fn input<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.inputs.push(path.into());
+        self
+    }
+
+    pub fn generate<W: Write>(&self, mut writer: W) -> Result<()> {
+        crate::generate_set(&mut writer, self)
+    }
+
+    pub fn generate_to<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let path = path.as_ref();
+        let mut code = Vec::new();
+        self.generate(&mut code)?;
+
+        if fs::read(path).ok().as_ref() != Some(&code) {
+            fs::write(path, code)?;
+        }
+
+        for input in self.inputs.iter() {
+            println!("cargo:rerun-if-changed={}", input.display());
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,148 @@
+use std::collections::{BTreeMap, HashMap};
+use std::io::Write;
+
+use itertools::Itertools;
+
+use crate::{Atom, Result};
+
+struct State<'a> {
+    next: BTreeMap<u8, usize>,
+    accept: Option<&'a Atom<'a>>,
+}
+
+fn state_type(states: usize) -> &'static str {
+    if states <= 0x100 {
+        "u8"
+    } else if states <= 0x1_0000 {
+        "u16"
+    } else {
+        "u32"
+    }
+}
+
+pub fn generate_exact<W: Write>(
+    writer: &mut W,
+    name: &str,
+    table: &[u8; 256],
+    atoms: Vec<(&[u8], &Atom)>,
+) -> Result<()> {
+    if atoms.is_empty() {
+        writeln!(writer, "None")?;
+        return Ok(());
+    }
+
+    // State 0 rejects everything and state 1 is the start state.
+    let mut states = vec![
+        State {
+            next: BTreeMap::new(),
+            accept: None,
+        },
+        State {
+            next: BTreeMap::new(),
+            accept: None,
+        },
+    ];
+
+    for &(key, atom) in atoms.iter() {
+        let mut state = 1;
+        for &b in key {
+            state = match states[state].next.get(&b) {
+                Some(&next) => next,
+                None => {
+                    let next = states.len();
+                    states.push(State {
+                        next: BTreeMap::new(),
+                        accept: None,
+                    });
+                    states[state].next.insert(b, next);
+                    next
+                }
+            };
+        }
+
+        states[state].accept = Some(atom);
+    }
+
+    // Bytes that lead to the same state from every state share a column in the transition table.
+    let mut classes = HashMap::new();
+    let mut class_of = [0; 256];
+    for b in 0..=255u8 {
+        let column = states
+            .iter()
+            .map(|state| state.next.get(&table[b as usize]).cloned().unwrap_or(0))
+            .collect_vec();
+
+        let len = classes.len();
+        class_of[b as usize] = *classes.entry(column).or_insert(len);
+    }
+
+    let classes_len = classes.len();
+    let mut columns = classes.into_iter().collect_vec();
+    columns.sort_by_key(|&(_, class)| class);
+
+    let max_len = atoms.iter().map(|&(key, _)| key.len()).max().unwrap_or(0);
+    let state_ty = state_type(states.len());
+
+    writeln!(
+        writer,
+        "\
+        const CLASSES: [u8; 256] = [{class_of}];
+        const TRANSITIONS: [{state_ty}; {transitions_len}] = [",
+        class_of = class_of.iter().join(", "),
+        state_ty = state_ty,
+        transitions_len = states.len() * classes_len
+    )?;
+
+    for index in 0..states.len() {
+        writeln!(
+            writer,
+            "{},",
+            columns.iter().map(|(column, _)| column[index]).join(", ")
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+        ];
+        const ACCEPT: [Option<{name}>; {states_len}] = [",
+        name = name,
+        states_len = states.len()
+    )?;
+
+    for state in states.iter() {
+        match state.accept {
+            Some(atom) => writeln!(writer, "Some({pattern}),", pattern = atom.pattern)?,
+            None => writeln!(writer, "None,")?,
+        }
+    }
+
+    writeln!(
+        writer,
+        "\
+        ];
+
+        if {too_long} {{
+            return None;
+        }}
+
+        let mut state = 1;
+        for &b in s {{
+            state = TRANSITIONS[{row} + CLASSES[b as usize] as usize] as usize;
+        }}
+
+        ACCEPT[state]",
+        too_long = if max_len == 0 {
+            "!s.is_empty()".to_owned()
+        } else {
+            format!("s.len() > {}", max_len)
+        },
+        row = if classes_len == 1 {
+            "state".to_owned()
+        } else {
+            format!("state * {}", classes_len)
+        }
+    )?;
+
+    Ok(())
+}
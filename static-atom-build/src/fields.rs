@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::io::Write;
+
+use heck::SnakeCase;
+
+use crate::names::is_ident;
+use crate::{AtomSet, Result};
+
+/// The Rust type of a per-atom field.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    any(feature = "toml", feature = "json"),
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum FieldType {
+    I8,
+    I16,
+    I32,
+    I64,
+    U8,
+    U16,
+    U32,
+    U64,
+    Usize,
+    F32,
+    F64,
+    Bool,
+    Str,
+}
+
+impl FieldType {
+    fn rust_type(self) -> &'static str {
+        match self {
+            FieldType::I8 => "i8",
+            FieldType::I16 => "i16",
+            FieldType::I32 => "i32",
+            FieldType::I64 => "i64",
+            FieldType::U8 => "u8",
+            FieldType::U16 => "u16",
+            FieldType::U32 => "u32",
+            FieldType::U64 => "u64",
+            FieldType::Usize => "usize",
+            FieldType::F32 => "f32",
+            FieldType::F64 => "f64",
+            FieldType::Bool => "bool",
+            FieldType::Str => "&'static str",
+        }
+    }
+
+    fn int_range(self) -> Option<(i128, i128)> {
+        match self {
+            FieldType::I8 => Some((i8::MIN.into(), i8::MAX.into())),
+            FieldType::I16 => Some((i16::MIN.into(), i16::MAX.into())),
+            FieldType::I32 => Some((i32::MIN.into(), i32::MAX.into())),
+            FieldType::I64 => Some((i64::MIN.into(), i64::MAX.into())),
+            FieldType::U8 => Some((0, u8::MAX.into())),
+            FieldType::U16 => Some((0, u16::MAX.into())),
+            FieldType::U32 => Some((0, u32::MAX.into())),
+            FieldType::U64 | FieldType::Usize => Some((0, u64::MAX.into())),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            FieldType::Str => "str",
+            ty => ty.rust_type(),
+        })
+    }
+}
+
+/// The value of a per-atom field, converted to the field's type when the set is generated.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FieldValue {
+    Int(i128),
+    Float(f64),
+    Bool(bool),
+    Str(String),
+}
+
+macro_rules! from_int {
+    ($($ty:ty),*) => {
+        $(
+            impl From<$ty> for FieldValue {
+                fn from(value: $ty) -> Self {
+                    FieldValue::Int(value as i128)
+                }
+            }
+        )*
+    };
+}
+
+from_int!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+impl From<f32> for FieldValue {
+    fn from(value: f32) -> Self {
+        FieldValue::Float(value.into())
+    }
+}
+
+impl From<f64> for FieldValue {
+    fn from(value: f64) -> Self {
+        FieldValue::Float(value)
+    }
+}
+
+impl From<bool> for FieldValue {
+    fn from(value: bool) -> Self {
+        FieldValue::Bool(value)
+    }
+}
+
+impl<'a> From<&'a str> for FieldValue {
+    fn from(value: &'a str) -> Self {
+        FieldValue::Str(value.to_owned())
+    }
+}
+
+impl From<String> for FieldValue {
+    fn from(value: String) -> Self {
+        FieldValue::Str(value)
+    }
+}
+
+impl fmt::Display for FieldValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FieldValue::Int(n) => write!(f, "{}", n),
+            FieldValue::Float(n) => write!(f, "{:?}", n),
+            FieldValue::Bool(b) => write!(f, "{}", b),
+            FieldValue::Str(s) => write!(f, "{:?}", s),
+        }
+    }
+}
+
+fn float_literal(ty: FieldType, value: f64) -> String {
+    if value.is_nan() {
+        format!("{}::NAN", ty)
+    } else if value.is_infinite() {
+        let sign = if value < 0.0 { "-" } else { "" };
+        format!("{}{}::INFINITY", sign, ty)
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+fn literal(ty: FieldType, value: &FieldValue) -> Option<String> {
+    match (ty, value) {
+        (FieldType::F32, &FieldValue::Float(n)) | (FieldType::F64, &FieldValue::Float(n)) => Some(float_literal(ty, n)),
+        (FieldType::F32, &FieldValue::Int(n)) | (FieldType::F64, &FieldValue::Int(n)) => {
+            Some(float_literal(ty, n as f64))
+        }
+        (FieldType::Bool, &FieldValue::Bool(b)) => Some(b.to_string()),
+        (FieldType::Str, FieldValue::Str(s)) => Some(format!("{:?}", s)),
+        (_, &FieldValue::Int(n)) => match ty.int_range() {
+            Some((min, max)) if min <= n && n <= max => Some(n.to_string()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_reserved(name: &str, set: &AtomSet) -> bool {
+    ["as_str", "from_bytes", "from_bytes_cold", "parse_prefix"].contains(&name)
+        || set
+            .visitors
+            .iter()
+            .any(|visitor| name == format!("visit_{}", visitor.to_snake_case()))
+}
+
+pub fn generate_fields<W: Write>(writer: &mut W, set: &AtomSet) -> Result<()> {
+    let name = &set.name[..];
+
+    let mut fields = HashMap::new();
+    for &(ref field, ty) in set.fields.iter() {
+        if !is_ident(field) || is_reserved(field, set) {
+            return Err(format!("{} cannot have a field named {:?}", name, field).into());
+        }
+
+        if fields.insert(&field[..], ty).is_some() {
+            return Err(format!("duplicate field {} in {}", field, name).into());
+        }
+    }
+
+    let mut values = HashMap::new();
+    for (atom, field, value) in set.values.iter() {
+        if !set.atoms.contains(atom) {
+            return Err(format!(
+                "value for {} refers to {:?}, which is not an atom in {}",
+                field, atom, name
+            )
+            .into());
+        }
+
+        let ty = *fields
+            .get(&field[..])
+            .ok_or_else(|| format!("{:?} in {} has a value for unknown field {}", atom, name, field))?;
+
+        let literal = literal(ty, value).ok_or_else(|| {
+            format!(
+                "field {} in {} is {}, which cannot hold {:?}'s value {}",
+                field, name, ty, atom, value
+            )
+        })?;
+
+        if values.insert((&atom[..], &field[..]), literal).is_some() {
+            return Err(format!("{:?} in {} has more than one value for field {}", atom, name, field).into());
+        }
+    }
+
+    if set.fields.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(writer, "impl {name} {{", name = name)?;
+
+    for (field, ty) in set.fields.iter() {
+        writeln!(
+            writer,
+            "\
+            pub const fn {field}(self) -> {ty} {{
+                const TABLE: [{table_ty}; {len}] = [",
+            field = field,
+            ty = ty.rust_type(),
+            table_ty = ty.rust_type().replace("'static ", ""),
+            len = set.atoms.len()
+        )?;
+
+        for atom in set.atoms.iter() {
+            let literal = values
+                .get(&(&atom[..], &field[..]))
+                .ok_or_else(|| format!("{:?} in {} has no value for field {}", atom, name, field))?;
+
+            writeln!(writer, "{literal}, // {atom:?}", literal = literal, atom = atom)?;
+        }
+
+        writeln!(
+            writer,
+            "\
+                ];
+
+                TABLE[self as usize]
+            }}"
+        )?;
+    }
+
+    writeln!(writer, "}}")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::generate_fields;
+    use crate::{AtomSet, FieldType};
+
+    fn generate(set: AtomSet) -> Result<String, String> {
+        let mut code = Vec::new();
+        generate_fields(&mut code, &set).map_err(|e| e.to_string())?;
+        Ok(String::from_utf8(code).unwrap())
+    }
+
+    fn small() -> AtomSet {
+        AtomSet::new("Small").atoms(&["BTC-EUR", "ETH-BTC"])
+    }
+
+    #[test]
+    fn tables() {
+        let code = generate(
+            small()
+                .field("tick_size", FieldType::F64)
+                .field("lot_size", FieldType::U64)
+                .field("quote", FieldType::Str)
+                .value("BTC-EUR", "tick_size", 0.01)
+                .value("ETH-BTC", "tick_size", 1)
+                .value("BTC-EUR", "lot_size", u64::MAX)
+                .value("ETH-BTC", "lot_size", 10u8)
+                .value("BTC-EUR", "quote", "EUR")
+                .value("ETH-BTC", "quote", "BTC"),
+        )
+        .unwrap();
+
+        let lines = code.lines().map(str::trim).collect::<Vec<_>>();
+        for expected in [
+            "pub const fn tick_size(self) -> f64 {",
+            "const TABLE: [f64; 2] = [",
+            "0.01, // \"BTC-EUR\"",
+            "1.0, // \"ETH-BTC\"",
+            "pub const fn lot_size(self) -> u64 {",
+            "18446744073709551615, // \"BTC-EUR\"",
+            "10, // \"ETH-BTC\"",
+            "pub const fn quote(self) -> &'static str {",
+            "const TABLE: [&str; 2] = [",
+            "\"EUR\", // \"BTC-EUR\"",
+        ]
+        .iter()
+        {
+            assert!(lines.contains(expected), "{:?} not in\n{}", expected, code);
+        }
+    }
+
+    #[test]
+    fn no_fields() {
+        assert_eq!(Ok(String::new()), generate(small()));
+    }
+
+    #[test]
+    fn errors() {
+        let lot_size = || small().field("lot_size", FieldType::U8);
+        assert_eq!(
+            Err("\"ETH-BTC\" in Small has no value for field lot_size".to_owned()),
+            generate(lot_size().value("BTC-EUR", "lot_size", 1))
+        );
+        assert_eq!(
+            Err("field lot_size in Small is u8, which cannot hold \"BTC-EUR\"'s value 256".to_owned()),
+            generate(lot_size().value("BTC-EUR", "lot_size", 256))
+        );
+        assert_eq!(
+            Err("field lot_size in Small is u8, which cannot hold \"BTC-EUR\"'s value \"1\"".to_owned()),
+            generate(lot_size().value("BTC-EUR", "lot_size", "1"))
+        );
+        assert_eq!(
+            Err("\"BTC-EUR\" in Small has a value for unknown field tick_size".to_owned()),
+            generate(lot_size().value("BTC-EUR", "tick_size", 0.01))
+        );
+        assert_eq!(
+            Err("value for lot_size refers to \"LTC-EUR\", which is not an atom in Small".to_owned()),
+            generate(lot_size().value("LTC-EUR", "lot_size", 1))
+        );
+        assert_eq!(
+            Err("Small cannot have a field named \"as_str\"".to_owned()),
+            generate(small().field("as_str", FieldType::Str))
+        );
+        assert_eq!(
+            Err("duplicate field lot_size in Small".to_owned()),
+            generate(lot_size().field("lot_size", FieldType::U32))
+        );
+    }
+}
@@ -1,211 +1,397 @@
 #![deny(warnings)]
 
-use std::collections::HashMap;
+mod bitset;
+mod builder;
+mod dfa;
+mod fields;
+mod load;
+mod names;
+mod phf;
+#[cfg(feature = "serde")]
+mod serde_impls;
+mod table;
+mod trie;
+mod weights;
+
+use std::collections::{HashMap, HashSet};
 use std::error;
-use std::fmt;
 use std::io::Write;
 use std::result;
-use std::str;
 
 use heck::SnakeCase;
 use itertools::Itertools;
 
-type Result<T> = result::Result<T, Box<error::Error>>;
+pub use crate::builder::{AtomSet, Backend};
+pub use crate::fields::{FieldType, FieldValue};
 
-struct ByteStrDisplay<'a>(&'a [u8]);
+type Result<T> = result::Result<T, Box<dyn error::Error>>;
 
-impl<'a> fmt::Display for ByteStrDisplay<'a> {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let Ok(s) = str::from_utf8(self.0) {
-            write!(f, "b{:?}", s)
+struct Atom<'a> {
+    s: &'a str,
+    aliases: Vec<&'a str>,
+    pattern: String,
+    type_name: String,
+    weight: u64,
+}
+
+impl<'a> Atom<'a> {
+    fn spellings(&self) -> impl Iterator<Item = &'a str> + '_ {
+        Some(self.s).into_iter().chain(self.aliases.iter().cloned())
+    }
+}
+
+struct Spec<'a> {
+    vis: &'a str,
+    name: &'a str,
+    atoms: Vec<Atom<'a>>,
+    case_insensitive: bool,
+    equivalents: &'a [Vec<u8>],
+    backend: Backend,
+    std: &'static str,
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    ignore_unknown_keys: bool,
+}
+
+fn fold_table(case_insensitive: bool, equivalents: &[Vec<u8>]) -> [u8; 256] {
+    let mut table = [0; 256];
+    for (b, t) in table.iter_mut().enumerate() {
+        let b = b as u8;
+        *t = if case_insensitive { b.to_ascii_lowercase() } else { b };
+    }
+
+    for class in equivalents.iter() {
+        let rep = if let Some(&b) = class.first() {
+            table[b as usize]
         } else {
-            write!(f, "&[{}]", itertools::join(self.0, ", "))
+            continue;
+        };
+
+        for &b in class.iter() {
+            let old = table[b as usize];
+            for t in table.iter_mut().filter(|t| **t == old) {
+                *t = rep;
+            }
         }
     }
+
+    table
 }
 
-fn generate_inner<W: Write>(writer: &mut W, lower_name: &str, atoms: Vec<(&[u8], &str)>) -> Result<()> {
-    for (_prefix_byte, atoms) in &atoms.into_iter().group_by(|&(s, _)| s[0]) {
-        let mut atoms = atoms.collect_vec();
-        let mut prefix = Vec::new();
+fn generate_types<W: Write>(writer: &mut W, spec: &Spec, type_names: &[String]) -> Result<()> {
+    writeln!(
+        writer,
+        "\
+        #[doc(hidden)]
+        {vis} mod _{lower_name}_types {{",
+        vis = spec.vis,
+        lower_name = spec.name.to_snake_case()
+    )?;
+
+    for (atom, type_name) in spec.atoms.iter().zip(type_names) {
+        writeln!(
+            writer,
+            "\
+            #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            pub struct {type_name}; // {s:?}",
+            type_name = type_name,
+            s = atom.s
+        )?;
+    }
 
-        let s = loop {
-            let &(bytes, s) = atoms.first().unwrap();
+    writeln!(writer, "}}")?;
+    Ok(())
+}
 
-            let prefix_byte = if let Some(&b) = bytes.first() {
-                b
-            } else {
-                break Some(s);
-            };
+fn generate_trie<W: Write>(
+    writer: &mut W,
+    spec: &Spec,
+    max_len: usize,
+    fast: Vec<(&[u8], &Atom)>,
+    by_len: HashMap<usize, Vec<(&[u8], &Atom)>>,
+    fallback: &str,
+) -> Result<()> {
+    if fast.is_empty() && by_len.is_empty() {
+        writeln!(writer, "{fallback}", fallback = fallback)?;
+        return Ok(());
+    }
 
-            if atoms.iter().all(|(s, _)| s[0] == prefix_byte) {
-                prefix.push(prefix_byte);
-                for (s, _) in atoms.iter_mut() {
-                    *s = &s[1..];
-                }
-            } else {
-                break None;
-            }
-        };
+    let masked = spec.case_insensitive && spec.equivalents.is_empty();
+    let uses_word = |len: usize| !masked && len > 0 && len <= 8;
+    if by_len.keys().any(|&len| len > 0 && !uses_word(len)) {
+        writeln!(
+            writer,
+            "use ::static_atom::{expect};",
+            expect = if masked { "ExpectMasked" } else { "Expect" }
+        )?;
+    }
 
+    if !spec.equivalents.is_empty() {
         writeln!(
             writer,
-            "if let Some(s) = s.expect({prefix}) {{",
-            prefix = ByteStrDisplay(&prefix[..])
+            "\
+                let mut buf = [0; {max_len}];
+                let buf = buf.get_mut(..s.len())?;
+                for (b, &c) in buf.iter_mut().zip(s) {{
+                    *b = Self::FOLD_TABLE[c as usize];
+                }}
+
+                let s = &buf[..];",
+            max_len = max_len
         )?;
+    }
 
-        if let Some(s) = s {
-            write!(writer, "Ok({lower_name}!({s:?}))", lower_name = lower_name, s = s)?;
+    // The heaviest atoms are compared outright, in order, before the decision tree.
+    for (bytes, atom) in fast {
+        if masked {
+            writeln!(
+                writer,
+                "if s.eq_ignore_ascii_case({bytes}) {{ return Some({pattern}); }}",
+                bytes = trie::ByteStrDisplay(bytes),
+                pattern = atom.pattern
+            )?;
         } else {
-            generate_inner(writer, lower_name, atoms)?;
+            writeln!(
+                writer,
+                "if s == {bytes} {{ return Some({pattern}); }}",
+                bytes = trie::ByteStrDisplay(bytes),
+                pattern = atom.pattern
+            )?;
         }
+    }
 
-        write!(writer, "}} else ")?;
+    writeln!(writer, "match s.len() {{")?;
+
+    for (len, mut atoms) in by_len.into_iter().sorted_by_key(|&(len, _)| len) {
+        writeln!(writer, "{len} => {{", len = len)?;
+        atoms.sort_by_key(|&(bytes, _)| bytes);
+        if uses_word(len) {
+            trie::generate_word(writer, atoms, fallback)?;
+        } else {
+            trie::generate_exact(writer, masked, atoms, fallback)?;
+        }
+        writeln!(writer, "}}")?;
     }
 
-    writeln!(writer, "{{ Err(()) }}")?;
+    writeln!(writer, "_ => {fallback} }}", fallback = fallback)?;
     Ok(())
 }
 
-pub fn generate<W: Write>(
-    mut writer: W,
-    mod_name: &str,
-    name: &str,
-    atoms: Vec<&str>,
-    visitors: Vec<&str>,
-) -> Result<()> {
-    let mod_name = if mod_name.is_empty() {
-        String::new()
-    } else {
-        mod_name.to_owned() + "::"
-    };
+fn generate_from_bytes<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    let Spec { name, ref atoms, .. } = *spec;
+    let table = fold_table(spec.case_insensitive, spec.equivalents);
+    let spellings = atoms
+        .iter()
+        .flat_map(|atom| atom.spellings().map(move |s| (s, atom)))
+        .collect_vec();
 
-    let lower_name = name.to_snake_case();
-    let visitors = visitors
-        .into_iter()
-        .map(|visitor| (visitor, visitor.to_snake_case()))
+    let keys = spellings
+        .iter()
+        .map(|&(s, _)| s.bytes().map(|b| table[b as usize]).collect_vec())
         .collect_vec();
 
+    let mut seen = HashMap::new();
+    for (key, &(s, _)) in keys.iter().zip(spellings.iter()) {
+        if let Some(other) = seen.insert(key, s) {
+            return Err(if other == s {
+                format!("duplicate atom {:?} in {}", s, name)
+            } else {
+                format!("atoms {:?} and {:?} in {} are equivalent", other, s, name)
+            }
+            .into());
+        }
+    }
+
+    let atoms = spellings.iter().map(|&(_, atom)| atom).collect_vec();
+
     let mut by_len = HashMap::new();
-    for &s in atoms.iter() {
-        let bytes = s.as_bytes();
-        by_len.entry(bytes.len()).or_insert_with(Vec::new).push((bytes, s));
+    for (key, &atom) in keys.iter().zip(atoms.iter()) {
+        by_len.entry(key.len()).or_insert_with(Vec::new).push((&key[..], atom));
     }
 
-    writeln!(
-        writer,
-        "\
-        #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-        pub enum {name} {{",
-        name = name
-    )?;
+    let max_len = by_len.keys().max().cloned().unwrap_or(0);
+    let masked = spec.case_insensitive && spec.equivalents.is_empty();
+    let expect = if masked { "ExpectMasked" } else { "Expect" };
+    writeln!(writer, "impl {name} {{", name = name)?;
 
-    for (index, &s) in atoms.iter().enumerate() {
-        writeln!(writer, "_{index}, // {s:?}", index = index, s = s)?;
+    let folded = spec.case_insensitive || !spec.equivalents.is_empty();
+    if folded {
+        writeln!(
+            writer,
+            "const FOLD_TABLE: [u8; 256] = [{table}];",
+            table = table.iter().join(", ")
+        )?;
     }
 
     writeln!(
         writer,
         "\
-        }}
-
-        #[doc(hide)]
-        pub mod _{lower_name}_types {{",
-        lower_name = lower_name
+            #[allow(unused_variables, clippy::manual_map)]
+            pub fn from_bytes(s: &[u8]) -> Option<Self> {{"
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
+    let mut cold_by_len = None;
+    match spec.backend {
+        Backend::Trie => match weights::hot(&spec.atoms.iter().map(|atom| atom.weight).collect_vec()) {
+            Some(hot) => {
+                let fast = weights::fast(&spec.atoms.iter().map(|atom| atom.weight).collect_vec())
+                    .into_iter()
+                    .map(|index| {
+                        let atom = &spec.atoms[index];
+                        (atom.s.bytes().map(|b| table[b as usize]).collect_vec(), atom)
+                    })
+                    .collect_vec();
+
+                let hot = spec
+                    .atoms
+                    .iter()
+                    .zip(hot)
+                    .filter_map(|(atom, hot)| if hot { Some(atom.s) } else { None })
+                    .collect::<HashSet<_>>();
+
+                let mut hot_by_len = HashMap::new();
+                let mut cold = HashMap::new();
+                for (len, atoms) in by_len {
+                    let (hot_atoms, cold_atoms) = atoms
+                        .into_iter()
+                        .filter(|&(bytes, _)| !fast.iter().any(|(key, _)| &key[..] == bytes))
+                        .partition::<Vec<_>, _>(|(_, atom)| hot.contains(atom.s));
+
+                    if !hot_atoms.is_empty() {
+                        hot_by_len.insert(len, hot_atoms);
+                    }
+
+                    if !cold_atoms.is_empty() {
+                        cold.insert(len, cold_atoms);
+                    }
+                }
+
+                let fast = fast.iter().map(|(key, atom)| (&key[..], *atom)).collect_vec();
+                generate_trie(writer, spec, max_len, fast, hot_by_len, "Self::from_bytes_cold(s)")?;
+                cold_by_len = Some(cold);
+            }
+            None => generate_trie(writer, spec, max_len, Vec::new(), by_len, "None")?,
+        },
+        Backend::Dfa => {
+            let atoms = keys.iter().map(|key| &key[..]).zip(atoms.iter().cloned()).collect_vec();
+            dfa::generate_exact(writer, name, &table, atoms)?;
+        }
+        Backend::PerfectHash => {
+            let mut atoms = keys.iter().map(|key| &key[..]).zip(atoms.iter().cloned()).collect_vec();
+            atoms.sort_by_key(|&(bytes, _)| bytes);
+            phf::generate_exact(writer, name, folded, atoms)?;
+        }
+    }
+
+    // Atoms outside the hot set, and input that matches no atom, are handled out of line so that the hot path stays
+    // small.
+    if let Some(cold_by_len) = cold_by_len {
         writeln!(
             writer,
             "\
-            #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-            pub struct _{index}; // {s:?}",
-            index = index,
-            s = s
+            }}
+
+            #[cold]
+            #[inline(never)]
+            #[allow(unused_variables, clippy::manual_map)]
+            fn from_bytes_cold(s: &[u8]) -> Option<Self> {{"
         )?;
+
+        generate_trie(writer, spec, max_len, Vec::new(), cold_by_len, "None")?;
     }
 
     writeln!(
         writer,
         "\
-        }}
+            }}
 
-        #[macro_export]
-        macro_rules! {lower_name} {{",
-        lower_name = lower_name
+            #[allow(unused_variables, clippy::manual_map)]
+            pub fn parse_prefix(s: &[u8]) -> Option<(Self, &[u8])> {{
+                use ::static_atom::{expect};
+",
+        expect = expect
     )?;
-    for (index, &s) in atoms.iter().enumerate() {
+
+    if !spec.equivalents.is_empty() {
         writeln!(
             writer,
-            "({s:?}) => {{ $crate::{mod_name}{name}::_{index} }};",
-            mod_name = mod_name,
-            name = name,
-            index = index,
-            s = s
+            "\
+                let input = s;
+                let mut buf = [0; {max_len}];
+                let buf = &mut buf[..s.len().min({max_len})];
+                for (b, &c) in buf.iter_mut().zip(s) {{
+                    *b = Self::FOLD_TABLE[c as usize];
+                }}
+
+                let s = &buf[..];
+                let found: Option<(Self, &[u8])> = {{",
+            max_len = max_len
         )?;
     }
 
-    writeln!(
-        writer,
-        "\
-        }}
+    let mut sorted = keys.iter().map(|key| &key[..]).zip(atoms).collect_vec();
+    sorted.sort_by_key(|&(bytes, _)| bytes);
+    trie::generate_prefix(writer, masked, sorted, "None", &mut 0)?;
 
-        #[macro_export]
-        macro_rules! {lower_name}_type {{",
-        lower_name = lower_name
-    )?;
-
-    for (index, &s) in atoms.iter().enumerate() {
+    if !spec.equivalents.is_empty() {
         writeln!(
             writer,
-            "({s:?}) => {{ $crate::{mod_name}_{lower_name}_types::_{index} }};",
-            mod_name = mod_name,
-            lower_name = lower_name,
-            index = index,
-            s = s
+            "\
+                }};
+
+                found.map(|(atom, rest)| (atom, &input[buf.len() - rest.len()..]))"
         )?;
     }
 
     writeln!(
         writer,
         "\
-         }}
+            }}
+        }}
 
-         impl ::std::str::FromStr for {name} {{
-            type Err = ();
+        impl {std}::str::FromStr for {name} {{
+            type Err = ::static_atom::ParseAtomError;
+
+            fn from_str(s: &str) -> {std}::result::Result<Self, ::static_atom::ParseAtomError> {{
+                <{name} as ::static_atom::TryFrom<&[u8]>>::try_from(s.as_bytes())
+            }}
+        }}
 
-            #[allow(unused_variables)]
-            fn from_str(s: &str) -> ::std::result::Result<Self, ()> {{
-                use ::static_atom::Expect;
+        impl<'a> ::static_atom::TryFrom<&'a [u8]> for {name} {{
+            type Err = ::static_atom::ParseAtomError;
 
-                let s = s.as_bytes();
-                match s.len() {{",
-        name = name
+            fn try_from(s: &'a [u8]) -> {std}::result::Result<Self, ::static_atom::ParseAtomError> {{
+                {name}::from_bytes(s).ok_or_else(|| {{
+                    ::static_atom::ParseAtomError::new(s, {name:?}, &[{spellings}], {table})
+                }})
+            }}
+        }}",
+        name = name,
+        std = spec.std,
+        spellings = spellings.iter().map(|&(s, _)| format!("{:?}", s)).join(", "),
+        table = if folded { "Some(&Self::FOLD_TABLE)" } else { "None" }
     )?;
 
-    for (len, mut atoms) in by_len.into_iter().sorted_by_key(|&(len, _)| len) {
-        writeln!(writer, "{len} => {{", len = len)?;
-        atoms.sort_by_key(|&(bytes, _)| bytes);
-        generate_inner(&mut writer, &lower_name, atoms)?;
-        writeln!(writer, "}}")?;
-    }
+    Ok(())
+}
+
+fn generate_impls<W: Write>(writer: &mut W, spec: &Spec, visitors: &[&str]) -> Result<()> {
+    let Spec {
+        vis, name, ref atoms, ..
+    } = *spec;
+
+    generate_from_bytes(writer, spec)?;
 
     writeln!(
         writer,
         "\
-                _ => Err(())
-                }}
-            }}
-        }}
-
         impl {name} {{
             pub fn as_str(&self) -> &'static str {{
                 match self {{",
         name = name
     )?;
 
-    for &s in atoms.iter() {
-        writeln!(writer, "{lower_name}!({s:?}) => {s:?},", lower_name = lower_name, s = s)?;
+    for atom in atoms.iter() {
+        writeln!(writer, "{pattern} => {s:?},", pattern = atom.pattern, s = atom.s)?;
     }
 
     writeln!(
@@ -215,22 +401,22 @@ pub fn generate<W: Write>(
             }}"
     )?;
 
-    for &(visitor, ref lower_visitor) in visitors.iter() {
+    for &visitor in visitors.iter() {
         writeln!(
             writer,
             "\
             pub fn visit_{lower_visitor}<V: {visitor}Visitor>(self, visitor: V) -> V::Value {{
                 match self {{",
             visitor = visitor,
-            lower_visitor = lower_visitor,
+            lower_visitor = visitor.to_snake_case(),
         )?;
 
-        for s in atoms.iter() {
+        for atom in atoms.iter() {
             writeln!(
                 writer,
-                "{lower_name}!({s:?}) => visitor.visit::<{lower_name}_type!({s:?})>(),",
-                lower_name = lower_name,
-                s = s,
+                "{pattern} => visitor.visit::<{type_name}>(),",
+                pattern = atom.pattern,
+                type_name = atom.type_name,
             )?;
         }
 
@@ -249,18 +435,18 @@ pub fn generate<W: Write>(
 
         impl From<{name}> for usize {{
             fn from(token: {name}) -> Self {{
+                usize::from(&token)
+            }}
+        }}
+
+        impl<'a> From<&'a {name}> for usize {{
+            fn from(token: &'a {name}) -> Self {{
                 match token {{",
         name = name
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
-        writeln!(
-            writer,
-            "{lower_name}!({s:?}) => {index},",
-            lower_name = lower_name,
-            index = index,
-            s = s
-        )?;
+    for (index, atom) in atoms.iter().enumerate() {
+        writeln!(writer, "{pattern} => {index},", pattern = atom.pattern, index = index)?;
     }
 
     writeln!(
@@ -278,13 +464,12 @@ pub fn generate<W: Write>(
         name = name
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
+    for (index, atom) in atoms.iter().enumerate() {
         writeln!(
             writer,
-            "{index} => Ok({lower_name}!({s:?})),",
-            lower_name = lower_name,
-            index = index,
-            s = s
+            "{index} => Ok({pattern}),",
+            pattern = atom.pattern,
+            index = index
         )?;
     }
 
@@ -298,112 +483,143 @@ pub fn generate<W: Write>(
     )?;
 
     #[cfg(feature = "serde")]
-    {
-        writeln!(
-            writer,
-            "\
-            impl ::serde::Serialize for {name} {{
-                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
-                    self.as_str().serialize(serializer)
-                }}
-            }}
-
-            impl<'de> ::serde::Deserialize<'de> for {name} {{
-                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
-                    use serde::de::Error;
-
-                    <&str>::deserialize(deserializer).and_then(|s| {{
-                        s.parse().map_err(|()| Error::custom(format!(\"can't parse {{}} as {name}\", s)))
-                    }})
-                }}
-            }}",
-            name = name
-        )?;
-    }
+    serde_impls::generate_atom(writer, spec)?;
 
     let where_mapping = atoms
         .iter()
-        .map(|&s| {
-            format!(
-                "M: ::static_atom::Mapping<{lower_name}_type!({s:?})>",
-                lower_name = lower_name,
-                s = s
-            )
-        })
+        .map(|atom| format!("M: ::static_atom::Mapping<{type_name}>", type_name = atom.type_name))
         .join("\n,");
 
     writeln!(
         writer,
         "\
-        impl ::std::fmt::Debug for {name} {{
-            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
-                write!(f, \"{lower_name}!({{}})\", self.as_str())
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        {vis} struct {name}Map<T>([Option<T>; {len}]);
+
+        impl<T> {name}Map<T> {{
+            const NONE: Option<T> = None;
+
+            pub const fn new() -> Self {{
+                {name}Map([Self::NONE; {len}])
+            }}
+
+            pub fn extend<I: {std}::iter::IntoIterator<Item = ({name}, T)>>(&mut self, iter: I) {{
+                for (key, value) in iter {{
+                    self.0[usize::from(key)] = Some(value);
+                }}
             }}
         }}
 
-        impl ::std::fmt::Display for {name} {{
-            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {{
-                f.write_str(self.as_str())
+        impl<T> Default for {name}Map<T> {{
+            fn default() -> Self {{
+                Self::new()
             }}
         }}
 
-        #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
-        pub struct {name}Map<T>([Option<T>; {len}]);
-        
-        impl<T> {name}Map<T> {{
-            pub fn new() -> Self {{
-                {name}Map(Default::default())
+        impl<T: {std}::fmt::Debug> {std}::fmt::Debug for {name}Map<T> {{
+            fn fmt(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                f.debug_map()
+                    .entries(::static_atom::iterators::Iter::<{name}, T>::new(&self.0).map(|(key, value)| (key.as_str(), value)))
+                    .finish()
+            }}
+        }}
+
+        impl<T> {std}::ops::Index<{name}> for {name}Map<T> {{
+            type Output = T;
+
+            fn index(&self, key: {name}) -> &T {{
+                self.0[usize::from(key)].as_ref().expect(\"no entry found for key\")
             }}
         }}
-        
+
+        impl<T> {std}::ops::IndexMut<{name}> for {name}Map<T> {{
+            fn index_mut(&mut self, key: {name}) -> &mut T {{
+                self.0[usize::from(key)].as_mut().expect(\"no entry found for key\")
+            }}
+        }}
+
+        impl<T> IntoIterator for {name}Map<T> {{
+            type Item = ({name}, T);
+            type IntoIter = ::static_atom::iterators::IntoIter<{name}, T, {len}>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                ::static_atom::iterators::IntoIter::new(self.0)
+            }}
+        }}
+
+        impl<'a, T> IntoIterator for &'a {name}Map<T> {{
+            type Item = ({name}, &'a T);
+            type IntoIter = ::static_atom::iterators::Iter<'a, {name}, T>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                ::static_atom::iterators::Iter::new(&self.0)
+            }}
+        }}
+
+        impl<'a, T> IntoIterator for &'a mut {name}Map<T> {{
+            type Item = ({name}, &'a mut T);
+            type IntoIter = ::static_atom::iterators::IterMut<'a, {name}, T>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                ::static_atom::iterators::IterMut::new(&mut self.0)
+            }}
+        }}
+
         impl<T> ::static_atom::AtomMap for {name}Map<T> {{
             type Key = {name};
             type Value = T;
-        
-            fn entry(&self, key: {name}) -> &Option<T> {{
+
+            fn key_index(key: &{name}) -> usize {{
+                usize::from(key)
+            }}
+
+            fn slot(&self, key: {name}) -> &Option<T> {{
                 &self.0[usize::from(key)]
             }}
-        
-            fn entry_mut(&mut self, key: {name}) -> &mut Option<T> {{
+
+            fn slot_mut(&mut self, key: {name}) -> &mut Option<T> {{
                 &mut self.0[usize::from(key)]
             }}
-        
+
             fn entries(&self) -> &[Option<T>] {{
                 &self.0
             }}
-        
+
             fn entries_mut(&mut self) -> &mut [Option<T>] {{
                 &mut self.0
             }}
         }}
 
-        impl<T> ::std::iter::FromIterator<({name}, T)> for {name}Map<T> {{
-            fn from_iter<I: ::std::iter::IntoIterator<Item = ({name}, T)>>(iter: I) -> Self {{
+        impl<T> {std}::iter::FromIterator<({name}, T)> for {name}Map<T> {{
+            fn from_iter<I: {std}::iter::IntoIterator<Item = ({name}, T)>>(iter: I) -> Self {{
                 let mut map = {name}Map::new();
-                for (key, value) in iter {{
-                    map.0[usize::from(key)] = Some(value);
-                }}
-
+                map.extend(iter);
                 map
             }}
         }}
 
-        pub struct Typed{name}Map<M>
+        impl<T> {std}::iter::Extend<({name}, T)> for {name}Map<T> {{
+            fn extend<I: {std}::iter::IntoIterator<Item = ({name}, T)>>(&mut self, iter: I) {{
+                {name}Map::extend(self, iter)
+            }}
+        }}
+
+        {vis} struct Typed{name}Map<M>
         where {where_mapping}
         {{",
-        lower_name = lower_name,
+        vis = vis,
         name = name,
         len = atoms.len(),
+        std = spec.std,
         where_mapping = where_mapping
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
+    for (index, atom) in atoms.iter().enumerate() {
         writeln!(
             writer,
-            "_{index}: Option<<M as ::static_atom::Mapping<{lower_name}_type!({s:?})>>::Value>,",
+            "_{index}: Option<<M as ::static_atom::Mapping<{type_name}>>::Value>,",
             index = index,
-            lower_name = lower_name,
-            s = s
+            type_name = atom.type_name
         )?;
     }
 
@@ -415,14 +631,15 @@ pub fn generate<W: Write>(
         impl<M> Typed{name}Map<M>
         where {where_mapping}
         {{
+            #[allow(clippy::new_without_default)]
             pub fn new() -> Self {{
                 Typed{name}Map {{",
         name = name,
         where_mapping = where_mapping
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
-        writeln!(writer, "_{index}: None, // {s:?}", index = index, s = s)?;
+    for (index, atom) in atoms.iter().enumerate() {
+        writeln!(writer, "_{index}: None, // {s:?}", index = index, s = atom.s)?;
     }
 
     writeln!(
@@ -440,24 +657,23 @@ pub fn generate<W: Write>(
                 M: ::static_atom::Mapping<A>,
                 A: 'static,
             {{
-                use std::any::TypeId;
-                use std::mem;
+                use {std}::any::TypeId;
 
                 let id = TypeId::of::<A>();",
         name = name,
+        std = spec.std,
         where_mapping = where_mapping
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
+    for (index, atom) in atoms.iter().enumerate() {
         write!(
             writer,
             "\
-            if id == TypeId::of::<{lower_name}_type!({s:?})>() {{
-                unsafe {{ mem::transmute(&self._{index}) }}
+            if id == TypeId::of::<{type_name}>() {{
+                unsafe {{ &*(&self._{index} as *const _ as *const Option<<M as ::static_atom::Mapping<A>>::Value>) }}
             }} else ",
-            lower_name = lower_name,
-            index = index,
-            s = s
+            type_name = atom.type_name,
+            index = index
         )?;
     }
 
@@ -474,22 +690,21 @@ pub fn generate<W: Write>(
                 M: ::static_atom::Mapping<A>,
                 A: 'static,
             {{
-                use std::any::TypeId;
-                use std::mem;
+                use {std}::any::TypeId;
 
-                let id = TypeId::of::<A>();"
+                let id = TypeId::of::<A>();",
+        std = spec.std
     )?;
 
-    for (index, &s) in atoms.iter().enumerate() {
+    for (index, atom) in atoms.iter().enumerate() {
         write!(
             writer,
             "\
-            if id == TypeId::of::<{lower_name}_type!({s:?})>() {{
-                unsafe {{ mem::transmute(&mut self._{index}) }}
+            if id == TypeId::of::<{type_name}>() {{
+                unsafe {{ &mut *(&mut self._{index} as *mut _ as *mut Option<<M as ::static_atom::Mapping<A>>::Value>) }}
             }} else ",
-            lower_name = lower_name,
-            index = index,
-            s = s
+            type_name = atom.type_name,
+            index = index
         )?;
     }
 
@@ -503,5 +718,239 @@ pub fn generate<W: Write>(
         }}"
     )?;
 
+    #[cfg(feature = "serde")]
+    serde_impls::generate_maps(writer, spec)?;
+
+    bitset::generate(writer, spec)?;
+    table::generate(writer, spec)
+}
+
+fn generate_set<W: Write>(writer: &mut W, set: &AtomSet) -> Result<()> {
+    let mod_name = if set.module.is_empty() {
+        String::new()
+    } else {
+        set.module.clone() + "::"
+    };
+
+    let name = &set.name[..];
+    let atoms = set.atoms.iter().map(String::as_str).collect_vec();
+    let visitors = set.visitors.iter().map(String::as_str).collect_vec();
+    let lower_name = name.to_snake_case();
+
+    let mut aliases = HashMap::new();
+    for (atom, alias) in set.aliases.iter() {
+        if !set.atoms.contains(atom) {
+            return Err(format!(
+                "alias {:?} refers to {:?}, which is not an atom in {}",
+                alias, atom, name
+            )
+            .into());
+        }
+
+        aliases.entry(&atom[..]).or_insert_with(Vec::new).push(&alias[..]);
+    }
+
+    let mut variant_attributes = HashMap::new();
+    for (atom, attribute) in set.variant_attributes.iter() {
+        if !set.atoms.contains(atom) {
+            return Err(format!(
+                "attribute #[{}] refers to {:?}, which is not an atom in {}",
+                attribute, atom, name
+            )
+            .into());
+        }
+
+        variant_attributes
+            .entry(&atom[..])
+            .or_insert_with(Vec::new)
+            .push(&attribute[..]);
+    }
+
+    let names = names::resolve(set)?;
+    let weights = weights::resolve(set)?;
+    let spec = Spec {
+        vis: "pub",
+        name,
+        case_insensitive: set.case_insensitive,
+        equivalents: &set.equivalents,
+        backend: set.backend,
+        std: if set.no_std { "::core" } else { "::std" },
+        ignore_unknown_keys: set.ignore_unknown_keys,
+        atoms: atoms
+            .iter()
+            .zip(weights)
+            .map(|(&s, weight)| Atom {
+                s,
+                aliases: aliases.get(s).cloned().unwrap_or_default(),
+                pattern: format!("{lower_name}!({s:?})", lower_name = lower_name, s = s),
+                type_name: format!("{lower_name}_type!({s:?})", lower_name = lower_name, s = s),
+                weight,
+            })
+            .collect(),
+    };
+
+    for attribute in set.attributes.iter() {
+        writeln!(writer, "#[{attribute}]", attribute = attribute)?;
+    }
+
+    writeln!(
+        writer,
+        "\
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum {name} {{",
+        name = name
+    )?;
+
+    for (variant, &s) in names.variants.iter().zip(atoms.iter()) {
+        for attribute in variant_attributes.get(s).cloned().unwrap_or_default() {
+            writeln!(writer, "#[{attribute}]", attribute = attribute)?;
+        }
+
+        writeln!(writer, "{variant}, // {s:?}", variant = variant, s = s)?;
+    }
+
+    writeln!(writer, "}}")?;
+
+    if names.consts.iter().any(Option::is_some) {
+        writeln!(writer, "impl {name} {{", name = name)?;
+
+        for (constant, variant) in names.consts.iter().zip(names.variants.iter()) {
+            if let Some(constant) = constant {
+                writeln!(
+                    writer,
+                    "pub const {constant}: {name} = {name}::{variant};",
+                    constant = constant,
+                    name = name,
+                    variant = variant
+                )?;
+            }
+        }
+
+        writeln!(writer, "}}")?;
+    }
+
+    fields::generate_fields(writer, set)?;
+    generate_types(writer, &spec, &names.variants)?;
+
+    writeln!(
+        writer,
+        "\
+        #[macro_export]
+        macro_rules! {lower_name} {{",
+        lower_name = lower_name
+    )?;
+
+    for (variant, s) in names
+        .variants
+        .iter()
+        .zip(spec.atoms.iter())
+        .flat_map(|(variant, atom)| atom.spellings().map(move |s| (variant, s)))
+    {
+        writeln!(
+            writer,
+            "({s:?}) => {{ $crate::{mod_name}{name}::{variant} }};",
+            mod_name = mod_name,
+            name = name,
+            variant = variant,
+            s = s
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+        }}
+
+        #[macro_export]
+        macro_rules! {lower_name}_type {{",
+        lower_name = lower_name
+    )?;
+
+    for (variant, s) in names
+        .variants
+        .iter()
+        .zip(spec.atoms.iter())
+        .flat_map(|(variant, atom)| atom.spellings().map(move |s| (variant, s)))
+    {
+        writeln!(
+            writer,
+            "({s:?}) => {{ $crate::{mod_name}_{lower_name}_types::{variant} }};",
+            mod_name = mod_name,
+            lower_name = lower_name,
+            variant = variant,
+            s = s
+        )?;
+    }
+
+    writeln!(writer, "}}")?;
+
+    generate_impls(writer, &spec, &visitors)?;
+
+    writeln!(
+        writer,
+        "\
+        impl {std}::fmt::Debug for {name} {{
+            fn fmt(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                write!(f, \"{lower_name}!({{}})\", self.as_str())
+            }}
+        }}
+
+        impl {std}::fmt::Display for {name} {{
+            fn fmt(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                f.write_str(self.as_str())
+            }}
+        }}",
+        lower_name = lower_name,
+        name = name,
+        std = spec.std
+    )?;
+
     Ok(())
 }
+
+pub fn generate<W: Write>(writer: W, mod_name: &str, name: &str, atoms: Vec<&str>, visitors: Vec<&str>) -> Result<()> {
+    AtomSet::new(name)
+        .module(mod_name)
+        .atoms(atoms)
+        .visitors(visitors)
+        .generate(writer)
+}
+
+#[doc(hidden)]
+pub fn generate_derive<W: Write>(
+    mut writer: W,
+    vis: &str,
+    name: &str,
+    variants: Vec<(&str, &str)>,
+    no_std: bool,
+    ignore_unknown_keys: bool,
+) -> Result<()> {
+    let lower_name = name.to_snake_case();
+    let spec = Spec {
+        vis,
+        name,
+        case_insensitive: false,
+        equivalents: &[],
+        backend: Backend::Trie,
+        std: if no_std { "::core" } else { "::std" },
+        ignore_unknown_keys,
+        atoms: variants
+            .iter()
+            .map(|&(variant, s)| Atom {
+                s,
+                aliases: Vec::new(),
+                pattern: format!("{name}::{variant}", name = name, variant = variant),
+                type_name: format!(
+                    "_{lower_name}_types::{variant}",
+                    lower_name = lower_name,
+                    variant = variant
+                ),
+                weight: 0,
+            })
+            .collect(),
+    };
+
+    let type_names = variants.iter().map(|&(variant, _)| variant.to_owned()).collect_vec();
+    generate_types(&mut writer, &spec, &type_names)?;
+    generate_impls(&mut writer, &spec, &[])
+}
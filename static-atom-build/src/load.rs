@@ -0,0 +1,410 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+use heck::CamelCase;
+
+use crate::{AtomSet, Result};
+#[cfg(any(feature = "toml", feature = "json"))]
+use crate::{Backend, FieldType, FieldValue};
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum DataValue {
+    Bool(bool),
+    Int(i64),
+    // Only reached for integers above `i64::MAX`, which JSON can hold but TOML cannot.
+    UInt(u64),
+    Float(f64),
+    Str(String),
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl From<DataValue> for FieldValue {
+    fn from(value: DataValue) -> Self {
+        match value {
+            DataValue::Bool(b) => b.into(),
+            DataValue::Int(n) => n.into(),
+            DataValue::UInt(n) => n.into(),
+            DataValue::Float(n) => n.into(),
+            DataValue::Str(s) => s.into(),
+        }
+    }
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+#[derive(serde::Deserialize)]
+#[serde(deny_unknown_fields)]
+struct SetFile {
+    name: Option<String>,
+    module: Option<String>,
+    #[serde(default)]
+    visitors: Vec<String>,
+    #[serde(default)]
+    case_insensitive: bool,
+    #[serde(default)]
+    equivalent: Vec<String>,
+    atoms: Vec<String>,
+    #[serde(default)]
+    aliases: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    named_variants: bool,
+    #[serde(default)]
+    variant_names: BTreeMap<String, String>,
+    backend: Option<Backend>,
+    #[serde(default)]
+    no_std: bool,
+    #[serde(default)]
+    ignore_unknown_keys: bool,
+    #[serde(default)]
+    fields: BTreeMap<String, FieldType>,
+    #[serde(default)]
+    data: BTreeMap<String, BTreeMap<String, DataValue>>,
+    #[serde(default)]
+    weights: BTreeMap<String, u64>,
+    sample: Option<String>,
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+impl SetFile {
+    fn into_atom_set(self, path: &Path, default_name: String) -> Result<AtomSet> {
+        let mut set = AtomSet::new(self.name.unwrap_or(default_name))
+            .atoms(self.atoms)
+            .visitors(self.visitors)
+            .case_insensitive(self.case_insensitive)
+            .named_variants(self.named_variants)
+            .no_std(self.no_std)
+            .ignore_unknown_keys(self.ignore_unknown_keys);
+
+        if let Some(backend) = self.backend {
+            set = set.backend(backend);
+        }
+
+        for bytes in self.equivalent {
+            set = set.equivalent(bytes);
+        }
+
+        for (atom, variant) in self.variant_names {
+            set = set.variant_name(atom, variant);
+        }
+
+        for (field, ty) in self.fields {
+            set = set.field(field, ty);
+        }
+
+        for (atom, values) in self.data {
+            for (field, value) in values {
+                set = set.value(&atom, field, value);
+            }
+        }
+
+        for (atom, aliases) in self.aliases {
+            for alias in aliases {
+                set = set.alias(&atom, alias);
+            }
+        }
+
+        for (atom, weight) in self.weights {
+            set = set.weight(atom, weight);
+        }
+
+        if let Some(sample) = self.sample {
+            let dir = path.parent().unwrap_or_else(|| Path::new(""));
+            set = set.weights_from_sample(dir.join(sample))?;
+        }
+
+        Ok(match self.module {
+            Some(module) => set.module(module),
+            None => set,
+        })
+    }
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e).into())
+}
+
+fn parse_lines(path: &Path, text: &str) -> Result<Vec<String>> {
+    let mut atoms = Vec::new();
+    let mut lines = HashMap::new();
+    for (index, line) in text.lines().enumerate() {
+        let atom = line.trim();
+        if atom.is_empty() || atom.starts_with('#') {
+            continue;
+        }
+
+        if let Some(first) = lines.insert(atom, index + 1) {
+            return Err(format!(
+                "{}:{}: duplicate atom {:?}, first listed on line {}",
+                path.display(),
+                index + 1,
+                atom,
+                first
+            )
+            .into());
+        }
+
+        atoms.push(atom.to_owned());
+    }
+
+    Ok(atoms)
+}
+
+fn count_lines(text: &str) -> BTreeMap<&str, u64> {
+    let mut counts = BTreeMap::new();
+    for line in text.lines() {
+        let input = line.trim();
+        if !input.is_empty() {
+            *counts.entry(input).or_insert(0) += 1;
+        }
+    }
+
+    counts
+}
+
+#[cfg(feature = "toml")]
+fn parse_toml(path: &Path, text: &str, default_name: String) -> Result<AtomSet> {
+    let file = toml::from_str::<SetFile>(text).map_err(|e| format!("{}: {}", path.display(), e))?;
+    file.into_atom_set(path, default_name)
+}
+
+#[cfg(not(feature = "toml"))]
+fn parse_toml(path: &Path, _text: &str, _default_name: String) -> Result<AtomSet> {
+    Err(format!(
+        "{}: enable the `toml` feature of static-atom-build to load TOML files",
+        path.display()
+    )
+    .into())
+}
+
+#[cfg(feature = "json")]
+fn parse_json(path: &Path, text: &str, default_name: String) -> Result<AtomSet> {
+    let map_err = |e: serde_json::Error| format!("{}: {}", path.display(), e);
+    if text.trim_start().starts_with('[') {
+        let atoms = serde_json::from_str::<Vec<String>>(text).map_err(map_err)?;
+        Ok(AtomSet::new(default_name).atoms(atoms))
+    } else {
+        let file = serde_json::from_str::<SetFile>(text).map_err(map_err)?;
+        file.into_atom_set(path, default_name)
+    }
+}
+
+#[cfg(not(feature = "json"))]
+fn parse_json(path: &Path, _text: &str, _default_name: String) -> Result<AtomSet> {
+    Err(format!(
+        "{}: enable the `json` feature of static-atom-build to load JSON files",
+        path.display()
+    )
+    .into())
+}
+
+impl AtomSet {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = read(path)?;
+        let default_name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_camel_case())
+            .unwrap_or_default();
+
+        let set = match path.extension().and_then(OsStr::to_str) {
+            Some("toml") => parse_toml(path, &text, default_name)?,
+            Some("json") => parse_json(path, &text, default_name)?,
+            _ => AtomSet::new(default_name).atoms(parse_lines(path, &text)?),
+        };
+
+        Ok(set.input(path))
+    }
+
+    pub fn atoms_from_file<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let atoms = parse_lines(path, &read(path)?)?;
+        Ok(self.atoms(atoms).input(path))
+    }
+
+    pub fn weights_from_sample<P: AsRef<Path>>(self, path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let text = read(path)?;
+        let mut set = self;
+        for (input, count) in count_lines(&text) {
+            set = set.observe(input, count);
+        }
+
+        Ok(set.input(path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::{env, fs, process};
+
+    use crate::AtomSet;
+
+    // A fresh directory for a test's files, so that tests can run in parallel.
+    fn scratch(test: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("static-atom-build-{}-{}", process::id(), test));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn lines_skip_comments_and_blank_lines() {
+        let text = "# pairs\nBTC-EUR\n\n  ETH-EUR  \n\t\n# ETH-BTC\nETH-BTC\n";
+        let atoms = super::parse_lines(Path::new("atoms.txt"), text).unwrap();
+        assert_eq!(atoms, ["BTC-EUR", "ETH-EUR", "ETH-BTC"]);
+    }
+
+    #[test]
+    fn lines_report_duplicates_with_line_numbers() {
+        let text = "BTC-EUR\n# comment\nETH-EUR\n\n ETH-EUR\n";
+        let error = super::parse_lines(Path::new("atoms.txt"), text).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "atoms.txt:5: duplicate atom \"ETH-EUR\", first listed on line 3"
+        );
+    }
+
+    #[test]
+    fn lines_count_samples() {
+        let counts = super::count_lines("BTC-EUR\nETH-EUR\n\n BTC-EUR \nXRP-EUR\n");
+        assert_eq!(
+            counts.into_iter().collect::<Vec<_>>(),
+            [("BTC-EUR", 2), ("ETH-EUR", 1), ("XRP-EUR", 1)]
+        );
+    }
+
+    #[test]
+    fn text_file_names_the_set_after_the_file() {
+        let dir = scratch("text");
+        let path = dir.join("currency_pairs.txt");
+        fs::write(&path, "BTC-EUR\nETH-EUR\n").unwrap();
+
+        let set = AtomSet::load(&path).unwrap();
+        assert_eq!(set.name, "CurrencyPairs");
+        assert_eq!(set.atoms, ["BTC-EUR", "ETH-EUR"]);
+        assert_eq!(set.inputs, [path]);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_file() {
+        let text = r#"
+            name = "Pairs"
+            module = "atoms"
+            atoms = ["BTC-EUR", "ETH-EUR"]
+            case_insensitive = true
+            equivalent = ["-/"]
+            backend = "dfa"
+            aliases = { "BTC-EUR" = ["XBT-EUR"] }
+            weights = { "BTC-EUR" = 10 }
+        "#;
+
+        let set = super::parse_toml(Path::new("pairs.toml"), text, "Default".to_owned()).unwrap();
+        assert_eq!(set.name, "Pairs");
+        assert_eq!(set.module, "atoms");
+        assert_eq!(set.atoms, ["BTC-EUR", "ETH-EUR"]);
+        assert!(set.case_insensitive);
+        assert_eq!(set.equivalents, [b"-/".to_vec()]);
+        assert_eq!(set.backend, crate::Backend::Dfa);
+        assert_eq!(set.aliases, [("BTC-EUR".to_owned(), "XBT-EUR".to_owned())]);
+        assert_eq!(set.weights, [("BTC-EUR".to_owned(), 10)]);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn toml_file_defaults_name_and_rejects_unknown_keys() {
+        let set = super::parse_toml(Path::new("pairs.toml"), "atoms = [\"a\"]", "Default".to_owned()).unwrap();
+        assert_eq!(set.name, "Default");
+        assert!(set.module.is_empty());
+
+        let error = super::parse_toml(Path::new("pairs.toml"), "atom = [\"a\"]", "Default".to_owned()).unwrap_err();
+        assert!(error.to_string().starts_with("pairs.toml: "), "{}", error);
+        assert!(error.to_string().contains("unknown field `atom`"), "{}", error);
+    }
+
+    #[cfg(feature = "toml")]
+    #[test]
+    fn sample_is_relative_to_the_set_file() {
+        let dir = scratch("sample");
+        fs::create_dir_all(dir.join("sets")).unwrap();
+        let path = dir.join("sets").join("pairs.toml");
+        let sample = dir.join("sets").join("sample.txt");
+        fs::write(&path, "atoms = [\"BTC-EUR\", \"ETH-EUR\"]\nsample = \"sample.txt\"\n").unwrap();
+        fs::write(&sample, "ETH-EUR\nETH-EUR\nBTC-EUR\n").unwrap();
+
+        let set = AtomSet::load(&path).unwrap();
+        assert_eq!(set.observed, [("BTC-EUR".to_owned(), 1), ("ETH-EUR".to_owned(), 2)]);
+        assert_eq!(set.inputs, [sample, path]);
+
+        fs::remove_file(dir.join("sets").join("sample.txt")).unwrap();
+        let error = AtomSet::load(dir.join("sets").join("pairs.toml")).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&dir.join("sets").join("sample.txt").display().to_string()),
+            "{}",
+            error
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_list() {
+        let set = super::parse_json(
+            Path::new("pairs.json"),
+            "[\"BTC-EUR\", \"ETH-EUR\"]",
+            "Pairs".to_owned(),
+        )
+        .unwrap();
+        assert_eq!(set.name, "Pairs");
+        assert_eq!(set.atoms, ["BTC-EUR", "ETH-EUR"]);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_file() {
+        let text =
+            r#"{ "name": "Sides", "atoms": ["buy", "sell"], "named_variants": true, "backend": "perfect_hash" }"#;
+        let set = super::parse_json(Path::new("sides.json"), text, "Default".to_owned()).unwrap();
+        assert_eq!(set.name, "Sides");
+        assert_eq!(set.atoms, ["buy", "sell"]);
+        assert!(set.named_variants);
+        assert_eq!(set.backend, crate::Backend::PerfectHash);
+
+        let error = super::parse_json(Path::new("sides.json"), "{ \"atoms\": 1 }", "Default".to_owned()).unwrap_err();
+        assert!(error.to_string().starts_with("sides.json: "), "{}", error);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn json_data() {
+        use crate::FieldValue;
+
+        let text = r#"{
+            "atoms": ["BTC-EUR"],
+            "fields": { "tick_size": "f64", "max_size": "u64", "min_size": "i64" },
+            "data": { "BTC-EUR": { "tick_size": 0.01, "max_size": 18446744073709551615, "min_size": -1 } }
+        }"#;
+        let set = super::parse_json(Path::new("pairs.json"), text, "Pairs".to_owned()).unwrap();
+        let mut values = set.values.clone();
+        values.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            values,
+            [
+                (
+                    "BTC-EUR".to_owned(),
+                    "max_size".to_owned(),
+                    FieldValue::Int(u64::MAX.into())
+                ),
+                ("BTC-EUR".to_owned(), "min_size".to_owned(), FieldValue::Int(-1)),
+                ("BTC-EUR".to_owned(), "tick_size".to_owned(), FieldValue::Float(0.01)),
+            ]
+        );
+    }
+}
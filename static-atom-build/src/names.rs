@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+
+use heck::{CamelCase, ShoutySnakeCase};
+
+use crate::{AtomSet, Result};
+
+const KEYWORDS: &[&str] = &[
+    "Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let", "loop", "macro",
+    "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return", "self", "static", "struct", "super",
+    "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+pub fn is_ident(s: &str) -> bool {
+    let mut chars = s.chars();
+    let first_ok = match chars.next() {
+        Some(c) => c == '_' || c.is_alphabetic(),
+        None => false,
+    };
+
+    first_ok && s != "_" && chars.all(|c| c == '_' || c.is_alphanumeric()) && !KEYWORDS.contains(&s)
+}
+
+// Leading digits are kept as they are, behind an underscore, and the rest of the atom is camel-cased, so that
+// "1INCH-EUR" becomes `_1InchEur`.
+fn derive_name(s: &str, set: &AtomSet) -> Result<String> {
+    if !s.chars().any(char::is_alphanumeric) {
+        return Err(format!(
+            "{:?} in {} has no letters or digits to name a variant after; name it explicitly, such as \
+             `Name = {:?}` in static_atom! or `variant_name({:?}, \"Name\")`",
+            s, set.name, s, s
+        )
+        .into());
+    }
+
+    let digits = s.len() - s.trim_start_matches(|c: char| c.is_numeric()).len();
+    let rest = s[digits..]
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect::<String>()
+        .to_camel_case();
+
+    if digits > 0 {
+        Ok(format!("_{}{}", &s[..digits], rest))
+    } else {
+        Ok(rest)
+    }
+}
+
+// Associated constants that the generated `impl {Name}` declares for its own use.
+fn is_reserved(constant: &str, set: &AtomSet) -> bool {
+    constant == "FOLD_TABLE" && (set.case_insensitive || !set.equivalents.is_empty())
+}
+
+pub struct Names {
+    pub variants: Vec<String>,
+    pub consts: Vec<Option<String>>,
+}
+
+pub fn resolve(set: &AtomSet) -> Result<Names> {
+    let mut explicit = HashMap::new();
+    for (atom, variant) in set.variant_names.iter() {
+        if !set.atoms.contains(atom) {
+            return Err(format!(
+                "variant name {} refers to {:?}, which is not an atom in {}",
+                variant, atom, set.name
+            )
+            .into());
+        }
+
+        explicit.insert(&atom[..], &variant[..]);
+    }
+
+    let mut variants = Vec::new();
+    let mut consts = Vec::new();
+    for (index, atom) in set.atoms.iter().enumerate() {
+        let variant = match explicit.get(&atom[..]) {
+            Some(&variant) => Some(variant.to_owned()),
+            None if set.named_variants => Some(derive_name(atom, set)?),
+            None => None,
+        };
+
+        if let Some(variant) = variant {
+            if !is_ident(&variant) {
+                return Err(format!("{:?} in {} cannot be named {:?}", atom, set.name, variant).into());
+            }
+
+            let mut constant = variant.to_shouty_snake_case();
+            if constant.starts_with(|c: char| c.is_numeric()) {
+                constant.insert(0, '_');
+            }
+
+            if is_reserved(&constant, set) {
+                return Err(format!(
+                    "{:?} in {} cannot have the constant {}, which the generated code uses",
+                    atom, set.name, constant
+                )
+                .into());
+            }
+
+            consts.push(Some(constant));
+            variants.push(variant);
+        } else {
+            consts.push(None);
+            variants.push(format!("_{}", index));
+        }
+    }
+
+    let mut seen = HashMap::new();
+    for (atom, variant) in set.atoms.iter().zip(variants.iter()) {
+        if let Some(other) = seen.insert(variant, atom) {
+            return Err(format!(
+                "{:?} and {:?} in {} would both be named {}",
+                other, atom, set.name, variant
+            )
+            .into());
+        }
+    }
+
+    let mut seen = HashMap::new();
+    for (atom, name) in set.atoms.iter().zip(consts.iter()) {
+        if let Some(name) = name {
+            if let Some(other) = seen.insert(name, atom) {
+                return Err(format!(
+                    "{:?} and {:?} in {} would both have the constant {}",
+                    other, atom, set.name, name
+                )
+                .into());
+            }
+        }
+    }
+
+    Ok(Names { variants, consts })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve;
+    use crate::AtomSet;
+
+    fn names(set: AtomSet) -> (Vec<String>, Vec<Option<String>>) {
+        let names = resolve(&set).unwrap();
+        (names.variants, names.consts)
+    }
+
+    fn error(set: AtomSet) -> String {
+        match resolve(&set) {
+            Ok(names) => panic!("expected an error, got {:?}", names.variants),
+            Err(e) => e.to_string(),
+        }
+    }
+
+    #[test]
+    fn unnamed() {
+        let (variants, consts) = names(AtomSet::new("Small").atoms(&["BTC-EUR", "ETH-BTC"]));
+        assert_eq!(variants, ["_0", "_1"]);
+        assert_eq!(consts, [None, None]);
+    }
+
+    #[test]
+    fn derived() {
+        let set = AtomSet::new("Small")
+            .atoms(&["BTC-EUR", "eth_btc", "1INCH-EUR", "2x", "123", "usd coin"])
+            .named_variants(true);
+
+        let (variants, consts) = names(set);
+        assert_eq!(variants, ["BtcEur", "EthBtc", "_1InchEur", "_2X", "_123", "UsdCoin"]);
+        assert_eq!(
+            consts,
+            ["BTC_EUR", "ETH_BTC", "_1INCH_EUR", "_2X", "_123", "USD_COIN"]
+                .iter()
+                .map(|s| Some(s.to_string()))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn explicit() {
+        let set = AtomSet::new("Small")
+            .atoms(&["BTC-EUR", "ETH-BTC"])
+            .variant_name("ETH-BTC", "EtherBitcoin");
+
+        let (variants, consts) = names(set);
+        assert_eq!(variants, ["_0", "EtherBitcoin"]);
+        assert_eq!(consts, [None, Some("ETHER_BITCOIN".to_owned())]);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            error(AtomSet::new("Small").atoms(&["BTC-EUR"]).variant_name("ETH-BTC", "Eth")),
+            "variant name Eth refers to \"ETH-BTC\", which is not an atom in Small"
+        );
+        assert_eq!(
+            error(AtomSet::new("Small").atoms(&["BTC-EUR"]).variant_name("BTC-EUR", "fn")),
+            "\"BTC-EUR\" in Small cannot be named \"fn\""
+        );
+        assert_eq!(
+            error(
+                AtomSet::new("Small")
+                    .atoms(&["BTC-EUR", "btc_eur"])
+                    .named_variants(true)
+            ),
+            "\"BTC-EUR\" and \"btc_eur\" in Small would both be named BtcEur"
+        );
+        assert_eq!(
+            error(
+                AtomSet::new("Small")
+                    .atoms(&["BTC-EUR", "ETH-BTC"])
+                    .variant_name("BTC-EUR", "BtcEur")
+                    .variant_name("ETH-BTC", "BTCEur")
+            ),
+            "\"BTC-EUR\" and \"ETH-BTC\" in Small would both have the constant BTC_EUR"
+        );
+        assert!(
+            error(AtomSet::new("Small").atoms(&["", "BTC-EUR"]).named_variants(true))
+                .starts_with("\"\" in Small has no letters or digits to name a variant after")
+        );
+        assert!(error(AtomSet::new("Small").atoms(&["--"]).named_variants(true)).contains("`Name = \"--\"`"));
+    }
+
+    #[test]
+    fn reserved() {
+        let set = || AtomSet::new("Small").atoms(&["fold table"]).named_variants(true);
+        assert_eq!(names(set()).0, ["FoldTable"]);
+        assert_eq!(
+            error(set().case_insensitive(true)),
+            "\"fold table\" in Small cannot have the constant FOLD_TABLE, which the generated code uses"
+        );
+        assert!(resolve(&set().equivalent("-_")).is_err());
+    }
+}
@@ -0,0 +1,164 @@
+use std::io::Write;
+
+use itertools::Itertools;
+use static_atom::phf::{displace, hash, Hashes};
+
+use crate::trie::ByteStrDisplay;
+use crate::{Atom, Result};
+
+const LAMBDA: usize = 5;
+
+struct Table {
+    seed: u64,
+    disps: Vec<(u32, u32)>,
+    slots: Vec<usize>,
+}
+
+fn try_generate(seed: u64, keys: &[&[u8]]) -> Option<Table> {
+    let hashes = keys.iter().map(|key| hash(seed, key.iter().cloned())).collect_vec();
+    let buckets_len = keys.len().div_ceil(LAMBDA);
+    let table_len = keys.len() as u32;
+
+    let mut buckets = vec![Vec::new(); buckets_len];
+    for (index, &Hashes { g, .. }) in hashes.iter().enumerate() {
+        buckets[g as usize % buckets_len].push(index);
+    }
+
+    let mut order = (0..buckets_len).collect_vec();
+    order.sort_by_key(|&bucket| usize::MAX - buckets[bucket].len());
+
+    let mut disps = vec![(0, 0); buckets_len];
+    let mut slots = vec![None; keys.len()];
+    let mut claimed = vec![0; keys.len()];
+    let mut generation = 0;
+
+    'buckets: for bucket in order {
+        let keys_in_bucket = &buckets[bucket];
+        for d1 in 0..table_len {
+            'disps: for d2 in 0..table_len {
+                generation += 1;
+                for &index in keys_in_bucket.iter() {
+                    let Hashes { f1, f2, .. } = hashes[index];
+                    let slot = (displace(f1, f2, d1, d2) % table_len) as usize;
+                    if slots[slot].is_some() || claimed[slot] == generation {
+                        continue 'disps;
+                    }
+
+                    claimed[slot] = generation;
+                }
+
+                for &index in keys_in_bucket.iter() {
+                    let Hashes { f1, f2, .. } = hashes[index];
+                    slots[(displace(f1, f2, d1, d2) % table_len) as usize] = Some(index);
+                }
+
+                disps[bucket] = (d1, d2);
+                continue 'buckets;
+            }
+        }
+
+        return None;
+    }
+
+    Some(Table {
+        seed,
+        disps,
+        slots: slots.into_iter().map(Option::unwrap).collect(),
+    })
+}
+
+fn generate_table(keys: &[&[u8]]) -> Table {
+    (0..)
+        .map(|n: u64| n.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .filter_map(|seed| try_generate(seed, keys))
+        .next()
+        .unwrap()
+}
+
+fn modulo(hash: &str, len: usize) -> String {
+    if len == 1 {
+        "0".to_owned()
+    } else {
+        format!("({} % {}) as usize", hash, len)
+    }
+}
+
+pub fn generate_exact<W: Write>(writer: &mut W, name: &str, folded: bool, atoms: Vec<(&[u8], &Atom)>) -> Result<()> {
+    if atoms.is_empty() {
+        writeln!(writer, "None")?;
+        return Ok(());
+    }
+
+    let keys = atoms.iter().map(|&(key, _)| key).collect_vec();
+    let table = generate_table(&keys);
+    let min_len = keys.iter().map(|key| key.len()).min().unwrap_or(0);
+    let max_len = keys.iter().map(|key| key.len()).max().unwrap_or(0);
+
+    writeln!(
+        writer,
+        "\
+        const DISPS: [(u32, u32); {buckets_len}] = [{disps}];
+        const ATOMS: [(&[u8], {name}); {len}] = [",
+        buckets_len = table.disps.len(),
+        disps = table
+            .disps
+            .iter()
+            .map(|&(d1, d2)| format!("({}, {})", d1, d2))
+            .join(", "),
+        name = name,
+        len = keys.len()
+    )?;
+
+    for &index in table.slots.iter() {
+        let (key, atom) = atoms[index];
+        writeln!(
+            writer,
+            "({key}, {pattern}),",
+            key = ByteStrDisplay(key),
+            pattern = atom.pattern
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+        ];
+
+        if {len_check} {{
+            return None;
+        }}
+
+        let hashes = ::static_atom::phf::hash({seed}, s.iter(){map});
+        let (d1, d2) = DISPS[{bucket}];
+        let (key, atom) = ATOMS[{slot}];",
+        len_check = if min_len > 0 {
+            format!("s.len() < {} || s.len() > {}", min_len, max_len)
+        } else {
+            format!("s.len() > {}", max_len)
+        },
+        seed = table.seed,
+        map = if folded {
+            ".map(|&b| Self::FOLD_TABLE[b as usize])"
+        } else {
+            ".cloned()"
+        },
+        bucket = modulo("hashes.g", table.disps.len()),
+        slot = modulo("::static_atom::phf::displace(hashes.f1, hashes.f2, d1, d2)", keys.len())
+    )?;
+
+    if folded {
+        writeln!(
+            writer,
+            "\
+            if key.len() == s.len() && s.iter().zip(key).all(|(&a, &b)| Self::FOLD_TABLE[a as usize] == b) {{
+                Some(atom)
+            }} else {{
+                None
+            }}"
+        )?;
+    } else {
+        writeln!(writer, "if key == s {{ Some(atom) }} else {{ None }}")?;
+    }
+
+    Ok(())
+}
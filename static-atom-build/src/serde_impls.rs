@@ -0,0 +1,310 @@
+use std::io::Write;
+
+use itertools::Itertools;
+
+use crate::{Result, Spec};
+
+/// Generates `Serialize` and `Deserialize` for the atom itself, as its string. Deserializing accepts borrowed, transient
+/// and owned strings as well as bytes, so that atoms can be read from any format.
+pub fn generate_atom<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    writeln!(
+        writer,
+        "\
+        impl ::serde::Serialize for {name} {{
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+                serializer.serialize_str(self.as_str())
+            }}
+        }}
+
+        impl<'de> ::serde::Deserialize<'de> for {name} {{
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+                struct Visitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for Visitor {{
+                    type Value = {name};
+
+                    fn expecting(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                        f.write_str(\"a {name} atom\")
+                    }}
+
+                    fn visit_str<E: ::serde::de::Error>(self, s: &str) -> Result<{name}, E> {{
+                        self.visit_bytes(s.as_bytes())
+                    }}
+
+                    fn visit_bytes<E: ::serde::de::Error>(self, s: &[u8]) -> Result<{name}, E> {{
+                        <{name} as ::static_atom::TryFrom<&[u8]>>::try_from(s).map_err(E::custom)
+                    }}
+                }}
+
+                deserializer.deserialize_str(Visitor)
+            }}
+        }}",
+        name = spec.name,
+        std = spec.std
+    )?;
+
+    Ok(())
+}
+
+// Declares `Key`, which deserializes to `None` for strings that are not atoms, so that maps can skip unknown keys.
+fn write_key<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    writeln!(
+        writer,
+        "\
+        struct Key(Option<{name}>);
+
+        impl<'de> ::serde::Deserialize<'de> for Key {{
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+                struct KeyVisitor;
+
+                impl<'de> ::serde::de::Visitor<'de> for KeyVisitor {{
+                    type Value = Key;
+
+                    fn expecting(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                        f.write_str(\"a string\")
+                    }}
+
+                    fn visit_str<E: ::serde::de::Error>(self, s: &str) -> Result<Key, E> {{
+                        Ok(Key({name}::from_bytes(s.as_bytes())))
+                    }}
+
+                    fn visit_bytes<E: ::serde::de::Error>(self, s: &[u8]) -> Result<Key, E> {{
+                        Ok(Key({name}::from_bytes(s)))
+                    }}
+                }}
+
+                deserializer.deserialize_str(KeyVisitor)
+            }}
+        }}",
+        name = spec.name,
+        std = spec.std
+    )?;
+
+    Ok(())
+}
+
+// Starts the loop over the entries of a map, binding `key` to the atom, or to `Option<atom>` when the set ignores
+// unknown keys.
+fn write_next_key<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    if spec.ignore_unknown_keys {
+        write_key(writer, spec)?;
+        writeln!(writer, "while let Some(Key(key)) = access.next_key::<Key>()? {{")?;
+    } else {
+        writeln!(
+            writer,
+            "while let Some(key) = access.next_key::<{name}>()? {{",
+            name = spec.name
+        )?;
+    }
+
+    writeln!(writer, "match key {{")?;
+    Ok(())
+}
+
+// Writes a match arm for an atom, given the pattern that matches it.
+fn write_arm<W: Write>(writer: &mut W, spec: &Spec, pattern: &str, body: &str) -> Result<()> {
+    if spec.ignore_unknown_keys {
+        writeln!(writer, "Some({pattern}) => {body},", pattern = pattern, body = body)?;
+    } else {
+        writeln!(writer, "{pattern} => {body},", pattern = pattern, body = body)?;
+    }
+
+    Ok(())
+}
+
+// Closes the loop started by `write_next_key`, skipping the values of unknown keys.
+fn write_end_key<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    if spec.ignore_unknown_keys {
+        writeln!(
+            writer,
+            "\
+            None => {{
+                access.next_value::<::serde::de::IgnoredAny>()?;
+            }}"
+        )?;
+    }
+
+    writeln!(writer, "}} }}")?;
+    Ok(())
+}
+
+/// Generates `Serialize` and `Deserialize` for `{Name}Map<T>` and `Typed{Name}Map<M>`, as maps keyed by `as_str()`.
+pub fn generate_maps<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    let Spec {
+        name, ref atoms, std, ..
+    } = *spec;
+
+    writeln!(
+        writer,
+        "\
+        impl<T: ::serde::Serialize> ::serde::Serialize for {name}Map<T> {{
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+                serializer.collect_map(
+                    ::static_atom::iterators::Iter::<{name}, T>::new(&self.0).map(|(key, value)| (key.as_str(), value)),
+                )
+            }}
+        }}
+
+        impl<'de, T: ::serde::Deserialize<'de>> ::serde::Deserialize<'de> for {name}Map<T> {{
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+                struct Visitor<T>({std}::marker::PhantomData<T>);
+
+                impl<'de, T: ::serde::Deserialize<'de>> ::serde::de::Visitor<'de> for Visitor<T> {{
+                    type Value = {name}Map<T>;
+
+                    fn expecting(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                        f.write_str(\"a map keyed by {name} atoms\")
+                    }}
+
+                    fn visit_map<A: ::serde::de::MapAccess<'de>>(self, mut access: A) -> Result<{name}Map<T>, A::Error> {{
+                        let mut map = {name}Map::new();",
+        name = name,
+        std = std
+    )?;
+
+    if spec.ignore_unknown_keys {
+        write_next_key(writer, spec)?;
+        write_arm(
+            writer,
+            spec,
+            "key",
+            "map.0[usize::from(key)] = Some(access.next_value()?)",
+        )?;
+        write_end_key(writer, spec)?;
+    } else {
+        writeln!(
+            writer,
+            "\
+            while let Some(key) = access.next_key::<{name}>()? {{
+                map.0[usize::from(key)] = Some(access.next_value()?);
+            }}",
+            name = name
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+                        Ok(map)
+                    }}
+                }}
+
+                deserializer.deserialize_map(Visitor({std}::marker::PhantomData))
+            }}
+        }}",
+        std = std
+    )?;
+
+    let where_mapping = atoms
+        .iter()
+        .map(|atom| format!("M: ::static_atom::Mapping<{type_name}>", type_name = atom.type_name))
+        .join(",\n");
+
+    let where_value = |bound: &str| {
+        atoms
+            .iter()
+            .map(|atom| {
+                format!(
+                    "<M as ::static_atom::Mapping<{type_name}>>::Value: {bound}",
+                    type_name = atom.type_name,
+                    bound = bound
+                )
+            })
+            .join(",\n")
+    };
+
+    writeln!(
+        writer,
+        "\
+        impl<M> ::serde::Serialize for Typed{name}Map<M>
+        where
+            {where_mapping},
+            {where_serialize}
+        {{
+            fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+                use ::serde::ser::SerializeMap;
+
+                let len = [{is_some}].iter().filter(|&&is_some| is_some).count();
+                let mut map = serializer.serialize_map(Some(len))?;",
+        name = name,
+        where_mapping = where_mapping,
+        where_serialize = where_value("::serde::Serialize"),
+        is_some = (0..atoms.len())
+            .map(|index| format!("self._{index}.is_some()", index = index))
+            .join(", ")
+    )?;
+
+    for (index, atom) in atoms.iter().enumerate() {
+        writeln!(
+            writer,
+            "\
+            if let Some(ref value) = self._{index} {{
+                map.serialize_entry({s:?}, value)?;
+            }}",
+            index = index,
+            s = atom.s
+        )?;
+    }
+
+    writeln!(
+        writer,
+        "\
+                map.end()
+            }}
+        }}
+
+        impl<'de, M> ::serde::Deserialize<'de> for Typed{name}Map<M>
+        where
+            {where_mapping},
+            {where_deserialize}
+        {{
+            fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+                struct Visitor<M>({std}::marker::PhantomData<M>);
+
+                impl<'de, M> ::serde::de::Visitor<'de> for Visitor<M>
+                where
+                    {where_mapping},
+                    {where_deserialize}
+                {{
+                    type Value = Typed{name}Map<M>;
+
+                    fn expecting(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                        f.write_str(\"a map keyed by {name} atoms\")
+                    }}
+
+                    fn visit_map<A: ::serde::de::MapAccess<'de>>(self, mut access: A) -> Result<Typed{name}Map<M>, A::Error> {{
+                        let mut map = Typed{name}Map::new();",
+        name = name,
+        std = std,
+        where_mapping = where_mapping,
+        where_deserialize = where_value("::serde::Deserialize<'de>")
+    )?;
+
+    write_next_key(writer, spec)?;
+
+    for (index, atom) in atoms.iter().enumerate() {
+        write_arm(
+            writer,
+            spec,
+            &atom.pattern,
+            &format!("map._{index} = Some(access.next_value()?)", index = index),
+        )?;
+    }
+
+    write_end_key(writer, spec)?;
+
+    writeln!(
+        writer,
+        "\
+                        Ok(map)
+                    }}
+                }}
+
+                deserializer.deserialize_map(Visitor({std}::marker::PhantomData))
+            }}
+        }}",
+        std = std
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::io::Write;
+
+use crate::{Result, Spec};
+
+/// Generates `{Name}Table<T>`, which holds a value for every atom.
+pub fn generate<W: Write>(writer: &mut W, spec: &Spec) -> Result<()> {
+    let Spec {
+        vis,
+        name,
+        ref atoms,
+        std,
+        ..
+    } = *spec;
+
+    writeln!(
+        writer,
+        "\
+        #[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        {vis} struct {name}Table<T>([T; {len}]);
+
+        impl<T> {name}Table<T> {{
+            pub fn from_fn<F: FnMut({name}) -> T>(mut f: F) -> Self {{
+                {name}Table({std}::array::from_fn(|index| {{
+                    f(<{name} as ::static_atom::TryFrom<usize>>::try_from(index).ok().unwrap())
+                }}))
+            }}
+
+            pub fn map<U, F: FnMut(T) -> U>(self, f: F) -> {name}Table<U> {{
+                {name}Table(self.0.map(f))
+            }}
+
+            pub fn zip<U>(self, other: {name}Table<U>) -> {name}Table<(T, U)> {{
+                let mut a = IntoIterator::into_iter(self.0);
+                let mut b = IntoIterator::into_iter(other.0);
+                {name}Table({std}::array::from_fn(|_| (a.next().unwrap(), b.next().unwrap())))
+            }}
+
+            pub fn iter(&self) -> ::static_atom::iterators::Keyed<{name}, {std}::slice::Iter<'_, T>> {{
+                ::static_atom::iterators::Keyed::new(self.0.iter())
+            }}
+
+            pub fn iter_mut(&mut self) -> ::static_atom::iterators::Keyed<{name}, {std}::slice::IterMut<'_, T>> {{
+                ::static_atom::iterators::Keyed::new(self.0.iter_mut())
+            }}
+
+            pub fn values(&self) -> {std}::slice::Iter<'_, T> {{
+                self.0.iter()
+            }}
+
+            pub fn values_mut(&mut self) -> {std}::slice::IterMut<'_, T> {{
+                self.0.iter_mut()
+            }}
+        }}
+
+        impl<T: Default> Default for {name}Table<T> {{
+            fn default() -> Self {{
+                Self::from_fn(|_| T::default())
+            }}
+        }}
+
+        impl<T: {std}::fmt::Debug> {std}::fmt::Debug for {name}Table<T> {{
+            fn fmt(&self, f: &mut {std}::fmt::Formatter) -> {std}::fmt::Result {{
+                f.debug_map().entries(self.iter().map(|(key, value)| (key.as_str(), value))).finish()
+            }}
+        }}
+
+        impl<T> {std}::ops::Index<{name}> for {name}Table<T> {{
+            type Output = T;
+
+            fn index(&self, key: {name}) -> &T {{
+                &self.0[usize::from(key)]
+            }}
+        }}
+
+        impl<T> {std}::ops::IndexMut<{name}> for {name}Table<T> {{
+            fn index_mut(&mut self, key: {name}) -> &mut T {{
+                &mut self.0[usize::from(key)]
+            }}
+        }}
+
+        impl<T> IntoIterator for {name}Table<T> {{
+            type Item = ({name}, T);
+            type IntoIter = ::static_atom::iterators::Keyed<{name}, {std}::array::IntoIter<T, {len}>>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                ::static_atom::iterators::Keyed::new(IntoIterator::into_iter(self.0))
+            }}
+        }}
+
+        impl<'a, T> IntoIterator for &'a {name}Table<T> {{
+            type Item = ({name}, &'a T);
+            type IntoIter = ::static_atom::iterators::Keyed<{name}, {std}::slice::Iter<'a, T>>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                self.iter()
+            }}
+        }}
+
+        impl<'a, T> IntoIterator for &'a mut {name}Table<T> {{
+            type Item = ({name}, &'a mut T);
+            type IntoIter = ::static_atom::iterators::Keyed<{name}, {std}::slice::IterMut<'a, T>>;
+
+            fn into_iter(self) -> Self::IntoIter {{
+                self.iter_mut()
+            }}
+        }}
+
+        impl<T> From<{name}Table<T>> for {name}Map<T> {{
+            fn from(table: {name}Table<T>) -> Self {{
+                {name}Map(table.0.map(Some))
+            }}
+        }}
+
+        impl<T> ::static_atom::TryFrom<{name}Map<T>> for {name}Table<T> {{
+            type Err = {name};
+
+            /// Fails with the first atom that has no value in the map.
+            fn try_from(map: {name}Map<T>) -> Result<Self, {name}> {{
+                if let Some(index) = map.0.iter().position(Option::is_none) {{
+                    return Err(<{name} as ::static_atom::TryFrom<usize>>::try_from(index).ok().unwrap());
+                }}
+
+                let mut entries = IntoIterator::into_iter(map.0);
+                Ok({name}Table({std}::array::from_fn(|_| entries.next().unwrap().unwrap())))
+            }}
+        }}",
+        vis = vis,
+        name = name,
+        len = atoms.len(),
+        std = std
+    )?;
+
+    Ok(())
+}
@@ -0,0 +1,186 @@
+use std::fmt;
+use std::io::Write;
+use std::str;
+
+use itertools::Itertools;
+
+use crate::{Atom, Result};
+
+pub struct ByteStrDisplay<'a>(pub &'a [u8]);
+
+impl<'a> fmt::Display for ByteStrDisplay<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Ok(s) = str::from_utf8(self.0) {
+            write!(f, "b{:?}", s)
+        } else {
+            write!(f, "&[{}]", itertools::join(self.0, ", "))
+        }
+    }
+}
+
+fn case_mask(prefix: &[u8]) -> Vec<u8> {
+    prefix
+        .iter()
+        .map(|b| if b.is_ascii_lowercase() { 0x20 } else { 0 })
+        .collect()
+}
+
+fn write_expect<W: Write>(writer: &mut W, masked: bool, prefix: &[u8]) -> Result<()> {
+    if masked {
+        writeln!(
+            writer,
+            "if let Some(s) = s.expect_masked({prefix}, {mask}) {{",
+            prefix = ByteStrDisplay(prefix),
+            mask = ByteStrDisplay(&case_mask(prefix)[..])
+        )?;
+    } else {
+        writeln!(
+            writer,
+            "if let Some(s) = s.expect({prefix}) {{",
+            prefix = ByteStrDisplay(prefix)
+        )?;
+    }
+
+    Ok(())
+}
+
+struct ByteDisplay(u8);
+
+impl fmt::Display for ByteDisplay {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            b'\'' | b'\\' => write!(f, "{}", self.0),
+            b if b.is_ascii_graphic() || b == b' ' => write!(f, "b'{}'", b as char),
+            b => write!(f, "{}", b),
+        }
+    }
+}
+
+// Picks the position whose byte splits the atoms into the most groups, preferring the smallest largest group on ties,
+// so that the tree is as shallow as possible.
+fn best_position(atoms: &[(&[u8], &Atom)]) -> usize {
+    let len = atoms[0].0.len();
+    (0..len)
+        .max_by_key(|&i| {
+            let mut counts = [0usize; 256];
+            for (bytes, _) in atoms.iter() {
+                counts[bytes[i] as usize] += 1;
+            }
+
+            let groups = counts.iter().filter(|&&count| count > 0).count();
+            let largest = counts.iter().max().cloned().unwrap_or(0);
+            (groups, usize::MAX - largest, usize::MAX - i)
+        })
+        .unwrap()
+}
+
+/// Generates a decision tree that matches atoms of the same length: each level switches on the byte that best
+/// discriminates the remaining atoms, and each leaf compares the whole input once. Inputs that match none of the atoms
+/// evaluate `fallback`.
+pub fn generate_exact<W: Write>(
+    writer: &mut W,
+    masked: bool,
+    mut atoms: Vec<(&[u8], &Atom)>,
+    fallback: &str,
+) -> Result<()> {
+    if atoms.len() == 1 {
+        let (bytes, atom) = atoms[0];
+        if bytes.is_empty() {
+            writeln!(writer, "Some({pattern})", pattern = atom.pattern)?;
+            return Ok(());
+        }
+
+        write_expect(writer, masked, bytes)?;
+        writeln!(
+            writer,
+            "Some({pattern}) }} else {{ {fallback} }}",
+            pattern = atom.pattern,
+            fallback = fallback
+        )?;
+        return Ok(());
+    }
+
+    let position = best_position(&atoms);
+    atoms.sort_by_key(|&(bytes, _)| bytes[position]);
+
+    if masked {
+        writeln!(
+            writer,
+            "match Self::FOLD_TABLE[s[{position}] as usize] {{",
+            position = position
+        )?;
+    } else {
+        writeln!(writer, "match s[{position}] {{", position = position)?;
+    }
+
+    for (b, atoms) in &atoms.into_iter().group_by(|&(bytes, _)| bytes[position]) {
+        writeln!(writer, "{b} => {{", b = ByteDisplay(b))?;
+        generate_exact(writer, masked, atoms.collect(), fallback)?;
+        writeln!(writer, "}}")?;
+    }
+
+    writeln!(writer, "_ => {fallback} }}", fallback = fallback)?;
+    Ok(())
+}
+
+pub fn generate_word<W: Write>(writer: &mut W, atoms: Vec<(&[u8], &Atom)>, fallback: &str) -> Result<()> {
+    writeln!(writer, "match ::static_atom::load_word(s) {{")?;
+
+    for (bytes, atom) in atoms {
+        writeln!(
+            writer,
+            "Some({word:#x}) => Some({pattern}), // {bytes}",
+            word = static_atom::load_word(bytes).unwrap(),
+            pattern = atom.pattern,
+            bytes = ByteStrDisplay(bytes)
+        )?;
+    }
+
+    writeln!(writer, "_ => {fallback} }}", fallback = fallback)?;
+    Ok(())
+}
+
+pub fn generate_prefix<W: Write>(
+    writer: &mut W,
+    masked: bool,
+    mut atoms: Vec<(&[u8], &Atom)>,
+    fallback: &str,
+    rest_count: &mut usize,
+) -> Result<()> {
+    let fallback = if atoms.first().is_some_and(|(s, _)| s.is_empty()) {
+        let (_, atom) = atoms.remove(0);
+        if atoms.is_empty() {
+            writeln!(writer, "Some(({pattern}, s))", pattern = atom.pattern)?;
+            return Ok(());
+        }
+
+        *rest_count += 1;
+        writeln!(writer, "let rest{n} = s;", n = rest_count)?;
+        format!("Some(({pattern}, rest{n}))", pattern = atom.pattern, n = rest_count)
+    } else {
+        fallback.to_owned()
+    };
+
+    for (_prefix_byte, atoms) in &atoms.into_iter().group_by(|&(s, _)| s[0]) {
+        let mut atoms = atoms.collect_vec();
+        let mut prefix = Vec::new();
+
+        while let Some(&prefix_byte) = atoms[0].0.first() {
+            if atoms.iter().all(|(s, _)| s.first() == Some(&prefix_byte)) {
+                prefix.push(prefix_byte);
+                for (s, _) in atoms.iter_mut() {
+                    *s = &s[1..];
+                }
+            } else {
+                break;
+            }
+        }
+
+        write_expect(writer, masked, &prefix)?;
+        generate_prefix(writer, masked, atoms, &fallback, rest_count)?;
+        write!(writer, "}} else ")?;
+    }
+
+    writeln!(writer, "{{ {fallback} }}", fallback = fallback)?;
+    Ok(())
+}
@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use crate::{AtomSet, Result};
+
+/// Resolves the weight of each atom in the set, in the order the atoms were declared. Weights given for an alias, and
+/// observed inputs that parse as an alias, count towards the atom. Observed inputs that match no atom are ignored.
+pub fn resolve(set: &AtomSet) -> Result<Vec<u64>> {
+    let table = crate::fold_table(set.case_insensitive, &set.equivalents);
+    let fold = |s: &str| s.bytes().map(|b| table[b as usize]).collect::<Vec<_>>();
+
+    let mut spellings = HashMap::new();
+    let mut keys = HashMap::new();
+    for (index, atom) in set.atoms.iter().enumerate() {
+        spellings.insert(&atom[..], index);
+        keys.insert(fold(atom), index);
+    }
+
+    for (atom, alias) in set.aliases.iter() {
+        if let Some(&index) = spellings.get(&atom[..]) {
+            spellings.insert(&alias[..], index);
+            keys.insert(fold(alias), index);
+        }
+    }
+
+    let mut weights = vec![0u64; set.atoms.len()];
+    for (atom, weight) in set.weights.iter() {
+        let index = *spellings.get(&atom[..]).ok_or_else(|| {
+            format!(
+                "weight {} refers to {:?}, which is not an atom in {}",
+                weight, atom, set.name
+            )
+        })?;
+
+        weights[index] = weights[index].saturating_add(*weight);
+    }
+
+    for (input, count) in set.observed.iter() {
+        if let Some(&index) = keys.get(&fold(input)) {
+            weights[index] = weights[index].saturating_add(*count);
+        }
+    }
+
+    Ok(weights)
+}
+
+/// Picks the atoms that the generated code should keep on its hot path: the heaviest atoms that between them account
+/// for 99% of the total weight. Returns `None` when no atom has a weight.
+pub fn hot(weights: &[u64]) -> Option<Vec<bool>> {
+    let total = weights.iter().map(|&weight| u128::from(weight)).sum::<u128>();
+    if total == 0 {
+        return None;
+    }
+
+    let mut order = (0..weights.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&index| u64::MAX - weights[index]);
+
+    let mut hot = vec![false; weights.len()];
+    let mut covered = 0;
+    for index in order {
+        if covered * 100 >= total * 99 {
+            break;
+        }
+
+        covered += u128::from(weights[index]);
+        hot[index] = true;
+    }
+
+    Some(hot)
+}
+
+/// Picks the atoms that the generated code should test for before anything else, heaviest first: at most `FAST_LEN` of
+/// the heaviest atoms, stopping once they account for half of the total weight.
+pub fn fast(weights: &[u64]) -> Vec<usize> {
+    const FAST_LEN: usize = 4;
+
+    let total = weights.iter().map(|&weight| u128::from(weight)).sum::<u128>();
+    let mut order = (0..weights.len())
+        .filter(|&index| weights[index] > 0)
+        .collect::<Vec<_>>();
+    order.sort_by_key(|&index| u64::MAX - weights[index]);
+
+    let mut fast = Vec::new();
+    let mut covered = 0;
+    for index in order.into_iter().take(FAST_LEN) {
+        if covered * 2 >= total {
+            break;
+        }
+
+        covered += u128::from(weights[index]);
+        fast.push(index);
+    }
+
+    fast
+}
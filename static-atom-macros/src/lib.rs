@@ -0,0 +1,355 @@
+#![deny(warnings)]
+
+extern crate proc_macro;
+
+use proc_macro::TokenStream;
+use proc_macro2::Span;
+use quote::ToTokens;
+use static_atom_build::{AtomSet, Backend};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{
+    braced, parse_macro_input, Attribute, Data, DeriveInput, Error, Expr, Fields, Ident, Item, Lit, LitStr, Meta, Path,
+    Result, Token, Visibility,
+};
+
+struct AtomInput {
+    attrs: Vec<Attribute>,
+    variant: Option<Ident>,
+    spellings: Punctuated<LitStr, Token![|]>,
+}
+
+impl Parse for AtomInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let variant = if input.peek(Ident) {
+            let variant = input.parse()?;
+            input.parse::<Token![=]>()?;
+            Some(variant)
+        } else {
+            None
+        };
+
+        let spellings = Punctuated::parse_separated_nonempty(input)?;
+        Ok(AtomInput {
+            attrs,
+            variant,
+            spellings,
+        })
+    }
+}
+
+struct AtomSetInput {
+    name: Ident,
+    set: AtomSet,
+    cfgs: Vec<Attribute>,
+}
+
+// Attributes that are passed through to the generated enum, or to the variant for an atom.
+fn is_forwarded(attr: &Attribute) -> bool {
+    attr.path().is_ident("doc") || attr.path().is_ident("allow")
+}
+
+fn apply_attr(set: AtomSet, attr: &Attribute) -> Result<AtomSet> {
+    if is_forwarded(attr) {
+        Ok(set.attribute(attr.meta.to_token_stream().to_string()))
+    } else if attr.path().is_ident("module") {
+        let path = attr.parse_args::<Path>()?;
+        let module = path
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>()
+            .join("::");
+
+        Ok(set.module(module))
+    } else if attr.path().is_ident("visitors") {
+        let idents = attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)?;
+        Ok(set.visitors(idents.iter().map(Ident::to_string)))
+    } else if attr.path().is_ident("backend") {
+        let ident = attr.parse_args::<Ident>()?;
+        let backend = if ident == "trie" {
+            Backend::Trie
+        } else if ident == "dfa" {
+            Backend::Dfa
+        } else if ident == "perfect_hash" {
+            Backend::PerfectHash
+        } else {
+            return Err(Error::new_spanned(ident, "expected `trie`, `dfa` or `perfect_hash`"));
+        };
+
+        Ok(set.backend(backend))
+    } else if attr.path().is_ident("named_variants") {
+        attr.meta.require_path_only()?;
+        Ok(set.named_variants(true))
+    } else if attr.path().is_ident("no_std") {
+        attr.meta.require_path_only()?;
+        Ok(set.no_std(true))
+    } else if attr.path().is_ident("ignore_unknown_keys") {
+        attr.meta.require_path_only()?;
+        Ok(set.ignore_unknown_keys(true))
+    } else if attr.path().is_ident("case_insensitive") {
+        attr.meta.require_path_only()?;
+        Ok(set.case_insensitive(true))
+    } else if attr.path().is_ident("equivalent") {
+        let bytes = attr.parse_args::<LitStr>()?;
+        Ok(set.equivalent(bytes.value()))
+    } else {
+        Err(Error::new_spanned(
+            attr,
+            "expected `#[module(...)]`, `#[visitors(...)]`, `#[backend(...)]`, `#[named_variants]`, `#[no_std]`, \
+             `#[ignore_unknown_keys]`, `#[case_insensitive]`, `#[equivalent(\"...\")]`, `#[doc]`, `#[allow]` or \
+             `#[cfg]`",
+        ))
+    }
+}
+
+impl Parse for AtomSetInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        match input.parse()? {
+            Visibility::Public(_) => {}
+            vis => {
+                return Err(Error::new_spanned(
+                    vis,
+                    "atom sets must be `pub` because their macros are exported from the crate",
+                ))
+            }
+        }
+
+        input.parse::<Token![enum]>()?;
+        let name = input.parse::<Ident>()?;
+
+        let content;
+        braced!(content in input);
+        let atoms = Punctuated::<AtomInput, Token![,]>::parse_terminated(&content)?;
+
+        let mut set = AtomSet::new(name.to_string());
+        for input in atoms.iter() {
+            let atom = input.spellings[0].value();
+            for alias in input.spellings.iter().skip(1) {
+                set = set.alias(&atom, alias.value());
+            }
+
+            if let Some(ref variant) = input.variant {
+                set = set.variant_name(&atom, variant.to_string());
+            }
+
+            for attr in input.attrs.iter() {
+                if attr.path().is_ident("cfg") {
+                    return Err(Error::new_spanned(
+                        attr,
+                        "`#[cfg]` cannot remove a single atom, because the generated code lists every atom; put it \
+                         on the set instead",
+                    ));
+                } else if !is_forwarded(attr) {
+                    return Err(Error::new_spanned(attr, "expected `#[doc]` or `#[allow]`"));
+                }
+
+                set = set.variant_attribute(&atom, attr.meta.to_token_stream().to_string());
+            }
+
+            set = set.atom(atom);
+        }
+
+        let mut cfgs = Vec::new();
+        for attr in attrs {
+            if attr.path().is_ident("cfg") {
+                cfgs.push(attr);
+            } else {
+                set = apply_attr(set, &attr)?;
+            }
+        }
+
+        Ok(AtomSetInput { name, set, cfgs })
+    }
+}
+
+struct AtomSets(Vec<AtomSetInput>);
+
+impl Parse for AtomSets {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let mut sets = Vec::new();
+        while !input.is_empty() {
+            sets.push(input.parse()?);
+        }
+
+        Ok(AtomSets(sets))
+    }
+}
+
+fn parse_tokens(code: Vec<u8>) -> Result<TokenStream> {
+    let code = String::from_utf8(code).map_err(|e| Error::new(Span::call_site(), e))?;
+    code.parse().map_err(|e| Error::new(Span::call_site(), e))
+}
+
+fn item_attrs(item: &mut Item) -> Option<&mut Vec<Attribute>> {
+    match item {
+        Item::Const(item) => Some(&mut item.attrs),
+        Item::Enum(item) => Some(&mut item.attrs),
+        Item::Fn(item) => Some(&mut item.attrs),
+        Item::Impl(item) => Some(&mut item.attrs),
+        Item::Macro(item) => Some(&mut item.attrs),
+        Item::Mod(item) => Some(&mut item.attrs),
+        Item::Static(item) => Some(&mut item.attrs),
+        Item::Struct(item) => Some(&mut item.attrs),
+        Item::Trait(item) => Some(&mut item.attrs),
+        Item::Type(item) => Some(&mut item.attrs),
+        Item::Use(item) => Some(&mut item.attrs),
+        _ => None,
+    }
+}
+
+// Puts the set's `#[cfg]` attributes on every item generated for it, so that the whole set comes and goes together.
+fn apply_cfgs(code: Vec<u8>, cfgs: &[Attribute]) -> Result<proc_macro2::TokenStream> {
+    let code = String::from_utf8(code).map_err(|e| Error::new(Span::call_site(), e))?;
+    let mut file = syn::parse_file(&code)?;
+    for item in file.items.iter_mut() {
+        let attrs = item_attrs(item).ok_or_else(|| Error::new(Span::call_site(), "unexpected generated item"))?;
+        attrs.splice(0..0, cfgs.iter().cloned());
+    }
+
+    Ok(file.into_token_stream())
+}
+
+fn expand(sets: AtomSets) -> Result<TokenStream> {
+    let mut tokens = proc_macro2::TokenStream::new();
+    for input in sets.0 {
+        let mut code = Vec::new();
+        input
+            .set
+            .generate(&mut code)
+            .map_err(|e| Error::new(input.name.span(), e))?;
+
+        if input.cfgs.is_empty() {
+            tokens.extend(proc_macro2::TokenStream::from(parse_tokens(code)?));
+        } else {
+            tokens.extend(apply_cfgs(code, &input.cfgs)?);
+        }
+    }
+
+    Ok(tokens.into())
+}
+
+fn atom_attr(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    let mut atom = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("atom")) {
+        let value = match attr.meta {
+            Meta::NameValue(ref meta) => match meta.value {
+                Expr::Lit(ref expr) => match expr.lit {
+                    Lit::Str(ref s) => Some(s.clone()),
+                    _ => None,
+                },
+                _ => None,
+            },
+            _ => None,
+        };
+
+        let value = value.ok_or_else(|| Error::new_spanned(attr, "expected `#[atom = \"...\"]`"))?;
+        if atom.replace(value).is_some() {
+            return Err(Error::new_spanned(attr, "duplicate `#[atom]` attribute"));
+        }
+    }
+
+    Ok(atom)
+}
+
+#[derive(Default)]
+struct EnumOptions {
+    no_std: bool,
+    ignore_unknown_keys: bool,
+}
+
+fn enum_options(attrs: &[Attribute]) -> Result<EnumOptions> {
+    let mut options = EnumOptions::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("atom")) {
+        for ident in attr.parse_args_with(Punctuated::<Ident, Token![,]>::parse_terminated)? {
+            if ident == "no_std" {
+                options.no_std = true;
+            } else if ident == "ignore_unknown_keys" {
+                options.ignore_unknown_keys = true;
+            } else {
+                return Err(Error::new_spanned(
+                    ident,
+                    "expected `#[atom(no_std)]` or `#[atom(ignore_unknown_keys)]`",
+                ));
+            }
+        }
+    }
+
+    Ok(options)
+}
+
+fn expand_derive(input: DeriveInput) -> Result<TokenStream> {
+    if input.generics.lt_token.is_some() || input.generics.where_clause.is_some() {
+        return Err(Error::new_spanned(input.generics, "atom enums cannot be generic"));
+    }
+
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "`StaticAtom` can only be derived for enums",
+            ))
+        }
+    };
+
+    let mut variants = Vec::new();
+    for variant in data.variants.iter() {
+        if !matches!(variant.fields, Fields::Unit) {
+            return Err(Error::new_spanned(&variant.fields, "atom variants cannot have fields"));
+        }
+
+        let atom = atom_attr(&variant.attrs)?
+            .ok_or_else(|| Error::new_spanned(variant, "missing `#[atom = \"...\"]` attribute"))?;
+
+        variants.push((variant.ident.to_string(), atom.value()));
+    }
+
+    let options = enum_options(&input.attrs)?;
+    let mut code = Vec::new();
+    static_atom_build::generate_derive(
+        &mut code,
+        &input.vis.to_token_stream().to_string(),
+        &input.ident.to_string(),
+        variants
+            .iter()
+            .map(|(variant, s)| (variant.as_str(), s.as_str()))
+            .collect(),
+        options.no_std,
+        options.ignore_unknown_keys,
+    )
+    .map_err(|e| Error::new(input.ident.span(), e))?;
+
+    parse_tokens(code)
+}
+
+/// Declares one or more atom sets, expanding to the same items as `static_atom_build::AtomSet`. Doc comments and
+/// `#[allow]` on a set or an atom are passed on to the enum or its variant, and `#[cfg]` on a set applies to every item
+/// generated for it.
+///
+/// ```ignore
+/// static_atom! {
+///     #[module(atoms)]
+///     #[visitors(Convention)]
+///     pub enum Small {
+///         "BTC-EUR" | "XBT-EUR",
+///         "BTC-USDC",
+///     }
+/// }
+/// ```
+#[proc_macro]
+pub fn static_atom(input: TokenStream) -> TokenStream {
+    let sets = parse_macro_input!(input as AtomSets);
+    expand(sets).unwrap_or_else(|e| e.to_compile_error().into())
+}
+
+/// Implements parsing, `as_str` and the `{Name}Map` and `Typed{Name}Map` types for an enum whose variants are
+/// labelled with `#[atom = "..."]`. Label the enum itself with `#[atom(no_std)]` to refer to `::core` instead of
+/// `::std`, and with `#[atom(ignore_unknown_keys)]` to skip unknown keys when deserializing maps.
+#[proc_macro_derive(StaticAtom, attributes(atom))]
+pub fn derive_static_atom(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_derive(input).unwrap_or_else(|e| e.to_compile_error().into())
+}
@@ -0,0 +1,119 @@
+/// A view into a single slot of an `AtomMap`, returned by `AtomMap::entry`.
+pub enum Entry<'a, Key, Value: 'a> {
+    Occupied(OccupiedEntry<'a, Key, Value>),
+    Vacant(VacantEntry<'a, Key, Value>),
+}
+
+impl<'a, Key, Value: 'a> Entry<'a, Key, Value> {
+    pub(crate) fn new(key: Key, slot: &'a mut Option<Value>) -> Self {
+        if slot.is_some() {
+            Entry::Occupied(OccupiedEntry { key, slot })
+        } else {
+            Entry::Vacant(VacantEntry { key, slot })
+        }
+    }
+
+    pub fn key(&self) -> &Key {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default),
+        }
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> Value>(self, default: F) -> &'a mut Value {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_insert_with_key<F: FnOnce(&Key) -> Value>(self, default: F) -> &'a mut Value {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let value = default(entry.key());
+                entry.insert(value)
+            }
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut Value
+    where
+        Value: Default,
+    {
+        self.or_insert_with(Value::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut Value)>(self, f: F) -> Self {
+        match self {
+            Entry::Occupied(mut entry) => {
+                f(entry.get_mut());
+                Entry::Occupied(entry)
+            }
+            Entry::Vacant(entry) => Entry::Vacant(entry),
+        }
+    }
+}
+
+/// An entry for a key that has a value.
+pub struct OccupiedEntry<'a, Key, Value: 'a> {
+    key: Key,
+    slot: &'a mut Option<Value>,
+}
+
+impl<'a, Key, Value: 'a> OccupiedEntry<'a, Key, Value> {
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn get(&self) -> &Value {
+        self.slot.as_ref().unwrap()
+    }
+
+    pub fn get_mut(&mut self) -> &mut Value {
+        self.slot.as_mut().unwrap()
+    }
+
+    pub fn into_mut(self) -> &'a mut Value {
+        self.slot.as_mut().unwrap()
+    }
+
+    pub fn insert(&mut self, value: Value) -> Value {
+        self.slot.replace(value).unwrap()
+    }
+
+    pub fn remove(self) -> Value {
+        self.slot.take().unwrap()
+    }
+
+    pub fn remove_entry(self) -> (Key, Value) {
+        (self.key, self.slot.take().unwrap())
+    }
+}
+
+/// An entry for a key that has no value.
+pub struct VacantEntry<'a, Key, Value: 'a> {
+    key: Key,
+    slot: &'a mut Option<Value>,
+}
+
+impl<'a, Key, Value: 'a> VacantEntry<'a, Key, Value> {
+    pub fn key(&self) -> &Key {
+        &self.key
+    }
+
+    pub fn into_key(self) -> Key {
+        self.key
+    }
+
+    pub fn insert(self, value: Value) -> &'a mut Value {
+        self.slot.insert(value)
+    }
+}
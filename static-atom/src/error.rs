@@ -0,0 +1,292 @@
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+
+use core::fmt;
+
+/// The error returned when a string is not one of the atoms in a set.
+///
+/// Constructing the error only copies the input; the offset and suggestion are worked out when they are asked for.
+#[derive(Clone, PartialEq, Eq)]
+pub struct ParseAtomError {
+    #[cfg(feature = "std")]
+    input: Vec<u8>,
+    #[cfg(not(feature = "std"))]
+    input: [u8; MAX_SUGGESTION_LEN],
+    len: usize,
+    set: &'static str,
+    spellings: &'static [&'static str],
+    table: Option<&'static [u8; 256]>,
+}
+
+fn fold(table: Option<&[u8; 256]>, b: u8) -> u8 {
+    match table {
+        Some(table) => table[b as usize],
+        None => b,
+    }
+}
+
+// Without `std` the row lives on the stack, and only this much of the input is kept, so spellings and inputs longer
+// than this are never suggested.
+#[cfg(not(feature = "std"))]
+const MAX_SUGGESTION_LEN: usize = 64;
+
+fn edit_distance(table: Option<&[u8; 256]>, a: &[u8], b: &[u8]) -> Option<usize> {
+    #[cfg(feature = "std")]
+    let mut row = vec![0; b.len() + 1];
+    #[cfg(not(feature = "std"))]
+    let mut row = [0; MAX_SUGGESTION_LEN + 1];
+
+    let row = row.get_mut(..=b.len())?;
+    for (j, r) in row.iter_mut().enumerate() {
+        *r = j;
+    }
+
+    for (i, &ca) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if fold(table, ca) == fold(table, cb) { 0 } else { 1 };
+            let next = (diagonal + cost).min(row[j] + 1).min(row[j + 1] + 1);
+            diagonal = row[j + 1];
+            row[j + 1] = next;
+        }
+    }
+
+    Some(row[b.len()])
+}
+
+impl ParseAtomError {
+    /// Records that `input` did not parse as any of `spellings`, which are compared after translating bytes through
+    /// `table`, if any. Called by generated code.
+    #[doc(hidden)]
+    #[cold]
+    pub fn new(
+        input: &[u8],
+        set: &'static str,
+        spellings: &'static [&'static str],
+        table: Option<&'static [u8; 256]>,
+    ) -> Self {
+        #[cfg(feature = "std")]
+        let kept = input.to_vec();
+        #[cfg(not(feature = "std"))]
+        let kept = {
+            let mut kept = [0; MAX_SUGGESTION_LEN];
+            let n = input.len().min(MAX_SUGGESTION_LEN);
+            kept[..n].copy_from_slice(&input[..n]);
+            kept
+        };
+
+        ParseAtomError {
+            input: kept,
+            len: input.len(),
+            set,
+            spellings,
+            table,
+        }
+    }
+
+    // The part of the input that was kept: all of it with `std`, and at most `MAX_SUGGESTION_LEN` bytes without.
+    #[cfg(feature = "std")]
+    fn kept(&self) -> &[u8] {
+        &self.input
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn kept(&self) -> &[u8] {
+        &self.input[..self.len.min(MAX_SUGGESTION_LEN)]
+    }
+
+    /// The text that failed to parse, with any invalid UTF-8 replaced.
+    #[cfg(feature = "std")]
+    pub fn input(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.input)
+    }
+
+    /// The name of the atom set.
+    pub fn set(&self) -> &'static str {
+        self.set
+    }
+
+    /// The length of the longest prefix that the input shares with any atom in the set.
+    ///
+    /// This is not necessarily where parsing stopped: the trie gives up at about this point, but the perfect hash and
+    /// DFA backends may reject the input before or after reading this far.
+    pub fn offset(&self) -> usize {
+        let table = self.table;
+        self.spellings
+            .iter()
+            .map(|s| {
+                self.kept()
+                    .iter()
+                    .zip(s.as_bytes())
+                    .take_while(|&(&a, &b)| fold(table, a) == fold(table, b))
+                    .count()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The atom closest to the input by edit distance, if any is close enough to be a likely typo.
+    pub fn suggestion(&self) -> Option<&'static str> {
+        let input = self.kept();
+        if input.len() < self.len {
+            return None;
+        }
+
+        self.spellings
+            .iter()
+            .filter_map(|&s| Some((edit_distance(self.table, input, s.as_bytes())?, s)))
+            .filter(|&(distance, s)| distance <= (s.len() / 3).max(1))
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, s)| s)
+    }
+}
+
+impl fmt::Debug for ParseAtomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut debug = f.debug_struct("ParseAtomError");
+        #[cfg(feature = "std")]
+        debug.field("input", &self.input());
+        debug
+            .field("set", &self.set)
+            .field("offset", &self.offset())
+            .field("suggestion", &self.suggestion())
+            .finish()
+    }
+}
+
+impl fmt::Display for ParseAtomError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        #[cfg(feature = "std")]
+        write!(f, "can't parse {:?} as {}: ", self.input(), self.set)?;
+        #[cfg(not(feature = "std"))]
+        write!(f, "can't parse input as {}: ", self.set)?;
+
+        let offset = self.offset();
+        if offset == self.len {
+            f.write_str("unexpected end of input")?;
+        } else {
+            write!(f, "unexpected byte at offset {}", offset)?;
+        }
+
+        if let Some(suggestion) = self.suggestion() {
+            write!(f, "; did you mean {:?}?", suggestion)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseAtomError {}
+
+#[cfg(test)]
+mod tests {
+    use super::ParseAtomError;
+
+    const SPELLINGS: &[&str] = &["BTC-EUR", "BTC-USDC", "ETH-EUR", "ETH-BTC"];
+
+    fn error(input: &str) -> ParseAtomError {
+        ParseAtomError::new(input.as_bytes(), "Small", SPELLINGS, None)
+    }
+
+    #[test]
+    fn offset() {
+        assert_eq!(0, error("").offset());
+        assert_eq!(0, error("XBT-EUR").offset());
+        assert_eq!(6, error("BTC-EUX").offset());
+        assert_eq!(6, error("ETH-BT").offset());
+        assert_eq!(7, error("BTC-EURC").offset());
+    }
+
+    #[test]
+    fn suggestion() {
+        assert_eq!(Some("BTC-EUR"), error("BTC-EUX").suggestion());
+        assert_eq!(Some("ETH-BTC"), error("ETH-BT").suggestion());
+        assert_eq!(Some("BTC-USDC"), error("BTC_USDC").suggestion());
+        assert_eq!(None, error("").suggestion());
+        assert_eq!(None, error("LTC-GBP").suggestion());
+    }
+
+    #[test]
+    fn suggestion_threshold() {
+        // A seven byte spelling allows two edits, and an eight byte spelling also allows two.
+        assert_eq!(Some("BTC-EUR"), error("BTX-EUX").suggestion());
+        assert_eq!(None, error("BTX-XUX").suggestion());
+        assert_eq!(Some("BTC-USDC"), error("BTC-UXDX").suggestion());
+        assert_eq!(None, error("BTC-XXXX").suggestion());
+
+        // Short spellings still allow one edit.
+        let error = ParseAtomError::new(b"bx", "Sides", &["by", "sell"], None);
+        assert_eq!(Some("by"), error.suggestion());
+    }
+
+    static LOWERCASE: [u8; 256] = {
+        let mut table = [0; 256];
+        let mut i = 0;
+        while i < 256 {
+            table[i] = (i as u8).to_ascii_lowercase();
+            i += 1;
+        }
+
+        table
+    };
+
+    #[test]
+    fn folded() {
+        let error = ParseAtomError::new(b"btc-eux", "Small", SPELLINGS, Some(&LOWERCASE));
+        assert_eq!(6, error.offset());
+        assert_eq!(Some("BTC-EUR"), error.suggestion());
+
+        let error = ParseAtomError::new(b"btc-eux", "Small", SPELLINGS, None);
+        assert_eq!(0, error.offset());
+        assert_eq!(None, error.suggestion());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn display() {
+        assert_eq!(
+            "can't parse \"BTC-EUX\" as Small: unexpected byte at offset 6; did you mean \"BTC-EUR\"?",
+            error("BTC-EUX").to_string()
+        );
+        assert_eq!(
+            "can't parse \"ETH-BT\" as Small: unexpected end of input; did you mean \"ETH-BTC\"?",
+            error("ETH-BT").to_string()
+        );
+        assert_eq!(
+            "can't parse \"LTC-GBP\" as Small: unexpected byte at offset 0",
+            error("LTC-GBP").to_string()
+        );
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[test]
+    fn truncated() {
+        extern crate std;
+        use std::string::ToString;
+
+        const LONG: &[&str] = &["0123456789012345678901234567890123456789012345678901234567890123456789"];
+
+        // The first 64 bytes are kept, which is enough for the offset but not for a suggestion.
+        let error = ParseAtomError::new(
+            b"0123456789012345678901234567890123456789012345678901234567890123456780",
+            "Long",
+            LONG,
+            None,
+        );
+        assert_eq!(64, error.offset());
+        assert_eq!(None, error.suggestion());
+        assert_eq!(
+            "can't parse input as Long: unexpected byte at offset 64",
+            error.to_string()
+        );
+
+        let error = ParseAtomError::new(b"BTC-EUX", "Small", SPELLINGS, None);
+        assert_eq!(Some("BTC-EUR"), error.suggestion());
+        assert_eq!(
+            "can't parse input as Small: unexpected byte at offset 6; did you mean \"BTC-EUR\"?",
+            error.to_string()
+        );
+    }
+}
@@ -1,7 +1,8 @@
-use std::marker::PhantomData;
-use std::slice;
+use core::array;
+use core::marker::PhantomData;
+use core::slice;
 
-use try_from::TryFrom;
+use crate::TryFrom;
 
 pub struct Iter<'a, Key, Value: 'a> {
     entries: slice::Iter<'a, Option<Value>>,
@@ -26,7 +27,7 @@ where
     type Item = (Key, &'a Value);
 
     fn next(&mut self) -> Option<(Key, &'a Value)> {
-        while let Some(opt) = self.entries.next() {
+        for opt in self.entries.by_ref() {
             let key = Key::try_from(self.index).ok().unwrap();
             self.index += 1;
 
@@ -62,7 +63,7 @@ where
     type Item = (Key, &'a mut Value);
 
     fn next(&mut self) -> Option<(Key, &'a mut Value)> {
-        while let Some(opt) = self.entries.next() {
+        for opt in self.entries.by_ref() {
             let key = Key::try_from(self.index).ok().unwrap();
             self.index += 1;
 
@@ -98,7 +99,7 @@ where
     type Item = Key;
 
     fn next(&mut self) -> Option<Key> {
-        while let Some(opt) = self.entries.next() {
+        for opt in self.entries.by_ref() {
             let key = Key::try_from(self.index).ok().unwrap();
             self.index += 1;
 
@@ -125,7 +126,7 @@ impl<'a, Value> Iterator for Values<'a, Value> {
     type Item = &'a Value;
 
     fn next(&mut self) -> Option<&'a Value> {
-        while let Some(opt) = self.entries.next() {
+        for opt in self.entries.by_ref() {
             if let Some(value) = opt.as_ref() {
                 return Some(value);
             }
@@ -134,3 +135,198 @@ impl<'a, Value> Iterator for Values<'a, Value> {
         None
     }
 }
+
+pub struct IntoIter<Key, Value, const N: usize> {
+    entries: array::IntoIter<Option<Value>, N>,
+    index: usize,
+    _pd: PhantomData<Key>,
+}
+
+impl<Key, Value, const N: usize> IntoIter<Key, Value, N> {
+    pub fn new(entries: [Option<Value>; N]) -> Self {
+        IntoIter {
+            entries: IntoIterator::into_iter(entries),
+            index: 0,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<Key, Value, const N: usize> Iterator for IntoIter<Key, Value, N>
+where
+    Key: TryFrom<usize>,
+{
+    type Item = (Key, Value);
+
+    fn next(&mut self) -> Option<(Key, Value)> {
+        for opt in self.entries.by_ref() {
+            let key = Key::try_from(self.index).ok().unwrap();
+            self.index += 1;
+
+            if let Some(value) = opt {
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+}
+
+pub struct ValuesMut<'a, Value: 'a> {
+    entries: slice::IterMut<'a, Option<Value>>,
+}
+
+impl<'a, Value: 'a> ValuesMut<'a, Value> {
+    pub fn new(slice: &'a mut [Option<Value>]) -> Self {
+        ValuesMut {
+            entries: slice.iter_mut(),
+        }
+    }
+}
+
+impl<'a, Value> Iterator for ValuesMut<'a, Value> {
+    type Item = &'a mut Value;
+
+    fn next(&mut self) -> Option<&'a mut Value> {
+        for opt in self.entries.by_ref() {
+            if let Some(value) = opt.as_mut() {
+                return Some(value);
+            }
+        }
+
+        None
+    }
+}
+
+/// Removes the entries from a map as it yields them. Entries that are not yielded are removed when the iterator is
+/// dropped.
+pub struct Drain<'a, Key, Value: 'a> {
+    entries: slice::IterMut<'a, Option<Value>>,
+    index: usize,
+    _pd: PhantomData<Key>,
+}
+
+impl<'a, Key, Value: 'a> Drain<'a, Key, Value> {
+    pub fn new(slice: &'a mut [Option<Value>]) -> Self {
+        Drain {
+            entries: slice.iter_mut(),
+            index: 0,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<'a, Key, Value> Iterator for Drain<'a, Key, Value>
+where
+    Key: TryFrom<usize>,
+{
+    type Item = (Key, Value);
+
+    fn next(&mut self) -> Option<(Key, Value)> {
+        for opt in self.entries.by_ref() {
+            let key = Key::try_from(self.index).ok().unwrap();
+            self.index += 1;
+
+            if let Some(value) = opt.take() {
+                return Some((key, value));
+            }
+        }
+
+        None
+    }
+}
+
+impl<'a, Key, Value> Drop for Drain<'a, Key, Value> {
+    fn drop(&mut self) {
+        for opt in self.entries.by_ref() {
+            *opt = None;
+        }
+    }
+}
+
+/// Pairs each item with the key for its index, for types that store a value for every key.
+pub struct Keyed<Key, I> {
+    inner: I,
+    index: usize,
+    _pd: PhantomData<Key>,
+}
+
+impl<Key, I> Keyed<Key, I> {
+    pub fn new(inner: I) -> Self {
+        Keyed {
+            inner,
+            index: 0,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<Key, I> Iterator for Keyed<Key, I>
+where
+    Key: TryFrom<usize>,
+    I: Iterator,
+{
+    type Item = (Key, I::Item);
+
+    fn next(&mut self) -> Option<(Key, I::Item)> {
+        let value = self.inner.next()?;
+        let key = Key::try_from(self.index).ok().unwrap();
+        self.index += 1;
+        Some((key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl<Key, I> ExactSizeIterator for Keyed<Key, I>
+where
+    Key: TryFrom<usize>,
+    I: ExactSizeIterator,
+{
+}
+
+pub struct Bits<Key, const N: usize> {
+    words: [u64; N],
+    index: usize,
+    _pd: PhantomData<Key>,
+}
+
+impl<Key, const N: usize> Bits<Key, N> {
+    pub fn new(words: [u64; N]) -> Self {
+        Bits {
+            words,
+            index: 0,
+            _pd: PhantomData,
+        }
+    }
+}
+
+impl<Key, const N: usize> Iterator for Bits<Key, N>
+where
+    Key: TryFrom<usize>,
+{
+    type Item = Key;
+
+    fn next(&mut self) -> Option<Key> {
+        while let Some(word) = self.words.get_mut(self.index) {
+            if *word != 0 {
+                let bit = word.trailing_zeros() as usize;
+                *word &= *word - 1;
+                return Some(Key::try_from(self.index * 64 + bit).ok().unwrap());
+            }
+
+            self.index += 1;
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.words.iter().map(|word| word.count_ones() as usize).sum();
+        (len, Some(len))
+    }
+}
+
+impl<Key, const N: usize> ExactSizeIterator for Bits<Key, N> where Key: TryFrom<usize> {}
@@ -1,41 +1,67 @@
+#![cfg_attr(not(feature = "std"), no_std)]
 #![deny(warnings)]
 
+pub mod entry;
+mod error;
 pub mod iterators;
+#[doc(hidden)]
+pub mod phf;
+mod wide;
 
-use std::iter::FromIterator;
-use std::mem;
+use core::convert::TryInto;
+use core::iter::FromIterator;
 
-use crate::iterators::{Iter, IterMut, Keys, Values};
+use crate::entry::Entry;
+use crate::iterators::{Drain, Iter, IterMut, Keys, Values, ValuesMut};
 
+pub use crate::error::ParseAtomError;
+#[cfg(feature = "std")]
 pub use try_from::TryFrom;
 
+/// Fallible conversion, with the same shape as `try_from::TryFrom`, which needs `std`.
+#[cfg(not(feature = "std"))]
+pub trait TryFrom<T>: Sized {
+    type Err;
+    fn try_from(value: T) -> Result<Self, Self::Err>;
+}
+
 pub trait AtomMap: FromIterator<(<Self as AtomMap>::Key, <Self as AtomMap>::Value)> {
     type Key: TryFrom<usize>;
     type Value;
 
-    fn entry(&self, key: Self::Key) -> &Option<Self::Value>;
-    fn entry_mut(&mut self, key: Self::Key) -> &mut Option<Self::Value>;
+    fn key_index(key: &Self::Key) -> usize;
+    fn slot(&self, key: Self::Key) -> &Option<Self::Value>;
+    fn slot_mut(&mut self, key: Self::Key) -> &mut Option<Self::Value>;
     fn entries(&self) -> &[Option<Self::Value>];
     fn entries_mut(&mut self) -> &mut [Option<Self::Value>];
 
+    fn entry(&mut self, key: Self::Key) -> Entry<'_, Self::Key, Self::Value> {
+        let index = Self::key_index(&key);
+        Entry::new(key, &mut self.entries_mut()[index])
+    }
+
     fn get(&self, key: Self::Key) -> Option<&Self::Value> {
-        self.entry(key).as_ref()
+        self.slot(key).as_ref()
     }
 
     fn get_mut(&mut self, key: Self::Key) -> Option<&mut Self::Value> {
-        self.entry_mut(key).as_mut()
+        self.slot_mut(key).as_mut()
+    }
+
+    fn contains_key(&self, key: Self::Key) -> bool {
+        self.slot(key).is_some()
     }
 
     fn insert(&mut self, key: Self::Key, value: Self::Value) -> Option<Self::Value> {
-        mem::replace(self.entry_mut(key), Some(value))
+        self.slot_mut(key).replace(value)
     }
 
     fn remove(&mut self, key: Self::Key) -> Option<Self::Value> {
-        mem::replace(self.entry_mut(key), None)
+        self.slot_mut(key).take()
     }
 
     fn get_or_insert(&mut self, key: Self::Key, value: Self::Value) -> &mut Self::Value {
-        let entry = self.entry_mut(key);
+        let entry = self.slot_mut(key);
         if entry.is_none() {
             *entry = Some(value);
         }
@@ -44,7 +70,7 @@ pub trait AtomMap: FromIterator<(<Self as AtomMap>::Key, <Self as AtomMap>::Valu
     }
 
     fn get_or_insert_with<F: FnOnce() -> Self::Value>(&mut self, key: Self::Key, f: F) -> &mut Self::Value {
-        let entry = self.entry_mut(key);
+        let entry = self.slot_mut(key);
         if entry.is_none() {
             *entry = Some(f());
         }
@@ -52,21 +78,59 @@ pub trait AtomMap: FromIterator<(<Self as AtomMap>::Key, <Self as AtomMap>::Valu
         entry.as_mut().unwrap()
     }
 
-    fn iter(&self) -> Iter<Self::Key, Self::Value> {
+    fn iter(&self) -> Iter<'_, Self::Key, Self::Value> {
         Iter::new(self.entries())
     }
 
-    fn iter_mut(&mut self) -> IterMut<Self::Key, Self::Value> {
+    fn iter_mut(&mut self) -> IterMut<'_, Self::Key, Self::Value> {
         IterMut::new(self.entries_mut())
     }
 
-    fn keys(&self) -> Keys<Self::Key, Self::Value> {
+    fn keys(&self) -> Keys<'_, Self::Key, Self::Value> {
         Keys::new(self.entries())
     }
 
-    fn values(&self) -> iterators::Values<Self::Value> {
+    fn values(&self) -> Values<'_, Self::Value> {
         Values::new(self.entries())
     }
+
+    fn values_mut(&mut self) -> ValuesMut<'_, Self::Value> {
+        ValuesMut::new(self.entries_mut())
+    }
+
+    fn len(&self) -> usize {
+        self.entries().iter().filter(|entry| entry.is_some()).count()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.entries().iter().all(Option::is_none)
+    }
+
+    fn clear(&mut self) {
+        for entry in self.entries_mut() {
+            *entry = None;
+        }
+    }
+
+    fn retain<F: FnMut(Self::Key, &mut Self::Value) -> bool>(&mut self, mut f: F) {
+        for (index, entry) in self.entries_mut().iter_mut().enumerate() {
+            if let Some(value) = entry {
+                if !f(Self::Key::try_from(index).ok().unwrap(), value) {
+                    *entry = None;
+                }
+            }
+        }
+    }
+
+    fn drain(&mut self) -> Drain<'_, Self::Key, Self::Value> {
+        Drain::new(self.entries_mut())
+    }
+
+    fn extend<I: IntoIterator<Item = (Self::Key, Self::Value)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
 }
 
 pub trait Mapping<Atom> {
@@ -105,7 +169,7 @@ pub trait TypedAtomMap<M> {
         M: Mapping<A>,
         A: 'static,
     {
-        mem::replace(self.entry_mut::<A>(), Some(value))
+        self.entry_mut::<A>().replace(value)
     }
 
     fn remove<A>(&mut self) -> Option<<M as Mapping<A>>::Value>
@@ -113,7 +177,7 @@ pub trait TypedAtomMap<M> {
         M: Mapping<A>,
         A: 'static,
     {
-        mem::replace(self.entry_mut::<A>(), None)
+        self.entry_mut::<A>().take()
     }
 
     fn get_or_insert<A>(&mut self, value: <M as Mapping<A>>::Value) -> &mut <M as Mapping<A>>::Value
@@ -144,11 +208,32 @@ pub trait TypedAtomMap<M> {
     }
 }
 
+/// Packs a string of 1 to 8 bytes into an integer with at most two loads, which overlap for lengths 3, 5, 6 and 7.
+/// Returns `None` for other lengths. `static-atom-build` calls this at build time to compute the constants that
+/// generated code matches the result against at run time, so it reads little-endian whatever the target.
+#[doc(hidden)]
+#[inline]
+pub fn load_word(s: &[u8]) -> Option<u64> {
+    let lo16 = |i: usize| u64::from(u16::from_le_bytes(s[i..i + 2].try_into().unwrap()));
+    let lo32 = |i: usize| u64::from(u32::from_le_bytes(s[i..i + 4].try_into().unwrap()));
+    let word = match s.len() {
+        1 => u64::from(s[0]),
+        2 => lo16(0),
+        3 => lo16(0) | lo16(1) << 16,
+        4 => lo32(0),
+        5..=7 => lo32(0) | lo32(s.len() - 4) << 32,
+        8 => u64::from_le_bytes(s.try_into().unwrap()),
+        _ => return None,
+    };
+
+    Some(word)
+}
+
 pub trait Expect<T>: Sized {
     fn expect(self, value: &T) -> Option<Self>;
 }
 
-impl<'a, T: PartialEq> Expect<T> for &'a [T] {
+impl<T: PartialEq> Expect<T> for &[T] {
     #[inline]
     fn expect(self, value: &T) -> Option<Self> {
         if let Some(b) = self.first() {
@@ -161,81 +246,152 @@ impl<'a, T: PartialEq> Expect<T> for &'a [T] {
     }
 }
 
-impl<'a> Expect<[u8; 1]> for &'a [u8] {
-    #[inline]
-    fn expect(self, a: &[u8; 1]) -> Option<Self> {
-        self.expect(&a[0])
+// The loads below go through `from_ne_bytes` on a byte array rather than reinterpreting the slice, so that they make
+// no assumptions about alignment. The optimiser still turns each one into a single unaligned load.
+
+#[inline]
+fn load_u16(s: &[u8]) -> u16 {
+    u16::from_ne_bytes([s[0], s[1]])
+}
+
+#[inline]
+fn load_u32(s: &[u8]) -> u32 {
+    u32::from_ne_bytes([s[0], s[1], s[2], s[3]])
+}
+
+#[inline]
+fn load_u64(s: &[u8]) -> u64 {
+    u64::from_ne_bytes([s[0], s[1], s[2], s[3], s[4], s[5], s[6], s[7]])
+}
+
+#[inline]
+fn expect_2<'a>(s: &'a [u8], a: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 2 && load_u16(s) == load_u16(a) {
+        Some(&s[2..])
+    } else {
+        None
     }
 }
 
-impl<'a> Expect<[u8; 2]> for &'a [u8] {
-    #[inline]
-    fn expect(self, a: &[u8; 2]) -> Option<Self> {
-        if self.len() < 2 {
-            return None;
-        }
+#[inline]
+fn expect_4<'a>(s: &'a [u8], a: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 4 && load_u32(s) == load_u32(a) {
+        Some(&s[4..])
+    } else {
+        None
+    }
+}
 
-        let s2 = unsafe { mem::transmute::<&[u8], &[u16]>(self) };
-        let a2 = unsafe { mem::transmute::<[u8; 2], u16>(*a) };
-        s2.expect(&a2)?;
-        Some(&self[2..])
+#[inline]
+fn expect_8<'a>(s: &'a [u8], a: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 8 && load_u64(s) == load_u64(a) {
+        Some(&s[8..])
+    } else {
+        None
     }
 }
 
-impl<'a> Expect<[u8; 3]> for &'a [u8] {
+impl<const N: usize> Expect<[u8; N]> for &[u8] {
     #[inline]
-    fn expect(self, a: &[u8; 3]) -> Option<Self> {
-        self.expect(&[a[0], a[1]])?.expect(&[a[2]])
+    fn expect(self, a: &[u8; N]) -> Option<Self> {
+        // N is a constant, so only one arm survives optimisation.
+        let a = &a[..];
+        match N {
+            0 => Some(self),
+            1 => self.expect(&a[0]),
+            2 => expect_2(self, a),
+            3 => expect_2(self, a)?.expect(&a[2]),
+            4 => expect_4(self, a),
+            5 => expect_4(self, a)?.expect(&a[4]),
+            6 => expect_2(expect_4(self, a)?, &a[4..]),
+            7 => expect_2(expect_4(self, a)?, &a[4..])?.expect(&a[6]),
+            8 => expect_8(self, a),
+            _ => {
+                if self.len() < N {
+                    return None;
+                }
+
+                let (s, rest) = self.split_at(N);
+                if wide::eq(s, a, None) {
+                    Some(rest)
+                } else {
+                    None
+                }
+            }
+        }
     }
 }
 
-impl<'a> Expect<[u8; 4]> for &'a [u8] {
+pub trait ExpectMasked<T>: Sized {
+    fn expect_masked(self, value: &T, mask: &T) -> Option<Self>;
+}
+
+impl ExpectMasked<u8> for &[u8] {
     #[inline]
-    fn expect(self, a: &[u8; 4]) -> Option<Self> {
-        if self.len() < 4 {
-            return None;
+    fn expect_masked(self, value: &u8, mask: &u8) -> Option<Self> {
+        if let Some(b) = self.first() {
+            if b | mask == *value {
+                return Some(&self[1..]);
+            }
         }
 
-        let s4 = unsafe { mem::transmute::<&[u8], &[u32]>(self) };
-        let a4 = unsafe { mem::transmute::<[u8; 4], u32>(*a) };
-        s4.expect(&a4)?;
-        Some(&self[4..])
+        None
     }
 }
 
-impl<'a> Expect<[u8; 5]> for &'a [u8] {
-    #[inline]
-    fn expect(self, a: &[u8; 5]) -> Option<Self> {
-        self.expect(&[a[0], a[1], a[2], a[3]])?.expect(&[a[4]])
+#[inline]
+fn expect_masked_2<'a>(s: &'a [u8], a: &[u8], m: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 2 && load_u16(s) | load_u16(m) == load_u16(a) {
+        Some(&s[2..])
+    } else {
+        None
     }
 }
 
-impl<'a> Expect<[u8; 6]> for &'a [u8] {
-    #[inline]
-    fn expect(self, a: &[u8; 6]) -> Option<Self> {
-        self.expect(&[a[0], a[1], a[2], a[3]])?.expect(&[a[4], a[5]])
+#[inline]
+fn expect_masked_4<'a>(s: &'a [u8], a: &[u8], m: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 4 && load_u32(s) | load_u32(m) == load_u32(a) {
+        Some(&s[4..])
+    } else {
+        None
     }
 }
 
-impl<'a> Expect<[u8; 7]> for &'a [u8] {
-    #[inline]
-    fn expect(self, a: &[u8; 7]) -> Option<Self> {
-        self.expect(&[a[0], a[1], a[2], a[3]])?
-            .expect(&[a[4], a[5]])?
-            .expect(&[a[6]])
+#[inline]
+fn expect_masked_8<'a>(s: &'a [u8], a: &[u8], m: &[u8]) -> Option<&'a [u8]> {
+    if s.len() >= 8 && load_u64(s) | load_u64(m) == load_u64(a) {
+        Some(&s[8..])
+    } else {
+        None
     }
 }
 
-impl<'a> Expect<[u8; 8]> for &'a [u8] {
+impl<const N: usize> ExpectMasked<[u8; N]> for &[u8] {
     #[inline]
-    fn expect(self, a: &[u8; 8]) -> Option<Self> {
-        if self.len() < 8 {
-            return None;
+    fn expect_masked(self, a: &[u8; N], m: &[u8; N]) -> Option<Self> {
+        let (a, m) = (&a[..], &m[..]);
+        match N {
+            0 => Some(self),
+            1 => self.expect_masked(&a[0], &m[0]),
+            2 => expect_masked_2(self, a, m),
+            3 => expect_masked_2(self, a, m)?.expect_masked(&a[2], &m[2]),
+            4 => expect_masked_4(self, a, m),
+            5 => expect_masked_4(self, a, m)?.expect_masked(&a[4], &m[4]),
+            6 => expect_masked_2(expect_masked_4(self, a, m)?, &a[4..], &m[4..]),
+            7 => expect_masked_2(expect_masked_4(self, a, m)?, &a[4..], &m[4..])?.expect_masked(&a[6], &m[6]),
+            8 => expect_masked_8(self, a, m),
+            _ => {
+                if self.len() < N {
+                    return None;
+                }
+
+                let (s, rest) = self.split_at(N);
+                if wide::eq(s, a, Some(m)) {
+                    Some(rest)
+                } else {
+                    None
+                }
+            }
         }
-
-        let s8 = unsafe { mem::transmute::<&[u8], &[u64]>(self) };
-        let a8 = unsafe { mem::transmute::<[u8; 8], u64>(*a) };
-        s8.expect(&a8)?;
-        Some(&self[8..])
     }
 }
@@ -0,0 +1,40 @@
+//! Hashing shared by `static-atom-build`, which searches for a perfect hash function at build time, and the
+//! `from_bytes` functions it generates.
+
+const K: u64 = 0x517c_c1b7_2722_0a95;
+
+pub struct Hashes {
+    pub g: u32,
+    pub f1: u32,
+    pub f2: u32,
+}
+
+#[inline]
+fn mix(mut h: u64) -> u64 {
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    h ^ (h >> 33)
+}
+
+#[inline]
+pub fn hash<I: IntoIterator<Item = u8>>(seed: u64, bytes: I) -> Hashes {
+    let mut h = seed;
+    for b in bytes {
+        h = (h.rotate_left(5) ^ u64::from(b)).wrapping_mul(K);
+    }
+
+    let h1 = mix(h);
+    let h2 = mix(h1 ^ K);
+    Hashes {
+        g: (h1 >> 32) as u32,
+        f1: h1 as u32,
+        f2: h2 as u32,
+    }
+}
+
+#[inline]
+pub fn displace(f1: u32, f2: u32, d1: u32, d2: u32) -> u32 {
+    d2.wrapping_add(f1.wrapping_mul(d1)).wrapping_add(f2)
+}
@@ -0,0 +1,140 @@
+//! Comparisons for `Expect` and `ExpectMasked` on arrays longer than 8 bytes. Each function takes slices of the same
+//! length, and compares `s | m` against `a` when a mask is given. Inputs that are not a multiple of the chunk size are
+//! finished off with one chunk that overlaps the previous one.
+
+use core::convert::TryInto;
+
+#[inline]
+fn load_u64(s: &[u8], i: usize) -> u64 {
+    u64::from_ne_bytes(s[i..i + 8].try_into().unwrap())
+}
+
+#[inline]
+fn eq_u64(s: &[u8], a: &[u8], m: Option<&[u8]>, i: usize) -> bool {
+    let mask = m.map_or(0, |m| load_u64(m, i));
+    load_u64(s, i) | mask == load_u64(a, i)
+}
+
+#[inline]
+fn eq_scalar(s: &[u8], a: &[u8], m: Option<&[u8]>) -> bool {
+    let len = s.len();
+    let mut eq = true;
+    let mut i = 0;
+    while i + 8 <= len {
+        eq &= eq_u64(s, a, m, i);
+        i += 8;
+    }
+
+    if i < len {
+        eq &= eq_u64(s, a, m, len - 8);
+    }
+
+    eq
+}
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2"))]
+mod sse2 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    #[inline]
+    fn eq_chunk(s: &[u8], a: &[u8], m: Option<&[u8]>, i: usize) -> bool {
+        let s = &s[i..i + 16];
+        let a = &a[i..i + 16];
+
+        // Safe because sse2 is enabled and each slice holds at least 16 bytes.
+        unsafe {
+            let mut v = _mm_loadu_si128(s.as_ptr() as *const __m128i);
+            if let Some(m) = m {
+                let m = &m[i..i + 16];
+                v = _mm_or_si128(v, _mm_loadu_si128(m.as_ptr() as *const __m128i));
+            }
+
+            let eq = _mm_cmpeq_epi8(v, _mm_loadu_si128(a.as_ptr() as *const __m128i));
+            _mm_movemask_epi8(eq) == 0xffff
+        }
+    }
+
+    #[inline]
+    pub fn eq(s: &[u8], a: &[u8], m: Option<&[u8]>) -> bool {
+        let len = s.len();
+        let mut eq = true;
+        let mut i = 0;
+        while i + 16 <= len {
+            eq &= eq_chunk(s, a, m, i);
+            i += 16;
+        }
+
+        if i < len {
+            eq &= eq_chunk(s, a, m, len - 16);
+        }
+
+        eq
+    }
+}
+
+#[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "avx2"))]
+mod avx2 {
+    #[cfg(target_arch = "x86")]
+    use core::arch::x86::*;
+    #[cfg(target_arch = "x86_64")]
+    use core::arch::x86_64::*;
+
+    #[inline]
+    fn eq_chunk(s: &[u8], a: &[u8], m: Option<&[u8]>, i: usize) -> bool {
+        let s = &s[i..i + 32];
+        let a = &a[i..i + 32];
+
+        // Safe because avx2 is enabled and each slice holds at least 32 bytes.
+        unsafe {
+            let mut v = _mm256_loadu_si256(s.as_ptr() as *const __m256i);
+            if let Some(m) = m {
+                let m = &m[i..i + 32];
+                v = _mm256_or_si256(v, _mm256_loadu_si256(m.as_ptr() as *const __m256i));
+            }
+
+            let eq = _mm256_cmpeq_epi8(v, _mm256_loadu_si256(a.as_ptr() as *const __m256i));
+            _mm256_movemask_epi8(eq) == -1
+        }
+    }
+
+    #[inline]
+    pub fn eq(s: &[u8], a: &[u8], m: Option<&[u8]>) -> bool {
+        let len = s.len();
+        let mut eq = true;
+        let mut i = 0;
+        while i + 32 <= len {
+            eq &= eq_chunk(s, a, m, i);
+            i += 32;
+        }
+
+        if i < len {
+            eq &= eq_chunk(s, a, m, len - 32);
+        }
+
+        eq
+    }
+}
+
+#[inline]
+pub fn eq(s: &[u8], a: &[u8], m: Option<&[u8]>) -> bool {
+    debug_assert!(s.len() == a.len() && s.len() > 8);
+
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "avx2"))]
+    {
+        if s.len() >= 32 {
+            return avx2::eq(s, a, m);
+        }
+    }
+
+    #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2"))]
+    {
+        if s.len() >= 16 {
+            return sse2::eq(s, a, m);
+        }
+    }
+
+    eq_scalar(s, a, m)
+}
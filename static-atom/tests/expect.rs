@@ -0,0 +1,163 @@
+//! Checks `Expect`, `ExpectMasked` and `load_word` against plain byte-by-byte comparisons, for every length the trie
+//! emits up to a few SIMD chunks and at every offset from an aligned address. Run under Miri with
+//! `cargo +nightly miri test` to check the loads for undefined behaviour.
+
+use static_atom::{Expect, ExpectMasked};
+
+// Offsets up to the widest load, an AVX2 vector, so that every length is tried at every alignment.
+const OFFSETS: usize = 32;
+
+// Which bytes to corrupt when checking for mismatches. Under Miri, which is much slower, only the first, middle and
+// last bytes: every position goes through the same loads.
+fn mismatches(n: usize) -> Vec<usize> {
+    if cfg!(miri) && n > 0 {
+        let mut positions = vec![0, n / 2, n - 1];
+        positions.dedup();
+        positions
+    } else {
+        (0..n).collect()
+    }
+}
+
+#[repr(align(32))]
+struct Aligned([u8; 128]);
+
+fn atom<const N: usize>() -> [u8; N] {
+    let mut a = [0; N];
+    for (i, b) in a.iter_mut().enumerate() {
+        *b = b'a' + (i % 26) as u8;
+    }
+
+    a
+}
+
+// Copies `bytes` into an aligned buffer at `offset`, followed by a trailing byte that the comparison must leave alone.
+fn place<'a>(buf: &'a mut Aligned, offset: usize, bytes: &[u8]) -> &'a [u8] {
+    let end = offset + bytes.len();
+    buf.0[offset..end].copy_from_slice(bytes);
+    buf.0[end] = b'!';
+    &buf.0[offset..=end]
+}
+
+fn check_expect<const N: usize>() {
+    let a = atom::<N>();
+    let mut buf = Aligned([0; 128]);
+    for offset in 0..OFFSETS {
+        let s = place(&mut buf, offset, &a);
+        assert_eq!(s.expect(&a), Some(&b"!"[..]), "length {} offset {}", N, offset);
+        assert_eq!(s[..N].expect(&a), Some(&b""[..]), "length {} offset {}", N, offset);
+
+        if N > 0 {
+            assert_eq!(s[..N - 1].expect(&a), None, "length {} offset {} truncated", N, offset);
+        }
+
+        for i in mismatches(N) {
+            let mut b = a;
+            b[i] ^= 1;
+            let s = place(&mut buf, offset, &b);
+            assert_eq!(s.expect(&a), None, "length {} offset {} byte {}", N, offset, i);
+        }
+    }
+}
+
+fn check_expect_masked<const N: usize>() {
+    let a = atom::<N>();
+    let m = [0x20; N];
+    let mut upper = a;
+    upper.make_ascii_uppercase();
+
+    let mut buf = Aligned([0; 128]);
+    for offset in 0..OFFSETS {
+        for input in [a, upper].iter() {
+            let s = place(&mut buf, offset, input);
+            assert_eq!(
+                s.expect_masked(&a, &m),
+                Some(&b"!"[..]),
+                "length {} offset {}",
+                N,
+                offset
+            );
+
+            if N > 0 {
+                assert_eq!(
+                    s[..N - 1].expect_masked(&a, &m),
+                    None,
+                    "length {} offset {} truncated",
+                    N,
+                    offset
+                );
+            }
+        }
+
+        for i in mismatches(N) {
+            let mut b = upper;
+            b[i] ^= 1;
+            let s = place(&mut buf, offset, &b);
+            assert_eq!(
+                s.expect_masked(&a, &m),
+                None,
+                "length {} offset {} byte {}",
+                N,
+                offset,
+                i
+            );
+        }
+    }
+}
+
+macro_rules! lengths {
+    ($($n:literal)*) => {
+        #[test]
+        fn expect_every_length() {
+            $(check_expect::<$n>();)*
+        }
+
+        #[test]
+        fn expect_masked_every_length() {
+            $(check_expect_masked::<$n>();)*
+        }
+    };
+}
+
+lengths!(
+    0 1 2 3 4 5 6 7 8 9 10 11 12 13 14 15 16 17 18 19 20 21 22 23 24 25 26 27 28 29 30 31 32 33 34 35 36 37 38 39 40
+    41 42 43 44 45 46 47 48 49 50 51 52 53 54 55 56 57 58 59 60 61 62 63 64 65 66 67 68 69 70 71 72
+);
+
+#[test]
+fn load_word_every_length() {
+    let mut buf = Aligned([0; 128]);
+    assert_eq!(static_atom::load_word(b""), None);
+    assert_eq!(static_atom::load_word(b"123456789"), None);
+
+    for n in 1..=8 {
+        let bytes = (1..=n as u8).collect::<Vec<_>>();
+        let expected = static_atom::load_word(&bytes);
+        assert!(expected.is_some(), "length {}", n);
+
+        for offset in 0..OFFSETS {
+            let s = place(&mut buf, offset, &bytes);
+            assert_eq!(
+                static_atom::load_word(&s[..n]),
+                expected,
+                "length {} offset {}",
+                n,
+                offset
+            );
+
+            for i in 0..n {
+                let mut b = bytes.clone();
+                b[i] ^= 1;
+                let s = place(&mut buf, offset, &b);
+                assert_ne!(
+                    static_atom::load_word(&s[..n]),
+                    expected,
+                    "length {} offset {} byte {}",
+                    n,
+                    offset,
+                    i
+                );
+            }
+        }
+    }
+}